@@ -0,0 +1,145 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::country::postal_country_name;
+
+/// A postal address.
+#[derive(Debug, Deserialize)]
+pub struct Address {
+    /// The street address, one entry per line.
+    #[serde(default)]
+    pub street: Vec<String>,
+    pub city: Option<String>,
+    /// The state, province, or county.
+    pub region: Option<String>,
+    pub postcode: Option<String>,
+    /// The country, as an ISO 3166-1 alpha-2 code.
+    pub country: Option<String>,
+}
+
+impl Address {
+    /// The lines of the address, in the order used by its country. The
+    /// country is written out in full, and left out if it's `home_country`.
+    pub fn lines(&self, home_country: Option<&str>) -> Vec<String> {
+        let city: &str = self.city.as_deref().unwrap_or_default();
+        let region: &str = self.region.as_deref().unwrap_or_default();
+        let postcode: &str = self.postcode.as_deref().unwrap_or_default();
+        let locality: Vec<String> = match self.country.as_deref() {
+            // Springfield, IL 62704
+            Some("US" | "CA") => vec![join(&[city, &join(&[region, postcode], " ")], ", ")],
+            // Sydney NSW 2000
+            Some("AU") => vec![join(&[city, region, postcode], " ")],
+            // City, county, and postcode on separate lines.
+            Some("GB" | "IE") => vec![city.to_string(), region.to_string(), postcode.to_string()],
+            // Auckland 1010
+            Some("NZ" | "SG" | "IN") => vec![join(&[city, postcode], " "), region.to_string()],
+            // 10115 Berlin
+            _ => vec![join(&[postcode, city], " "), region.to_string()],
+        };
+        self.street
+            .iter()
+            .cloned()
+            .chain(locality)
+            .chain(
+                self.country
+                    .as_deref()
+                    .filter(|country| Some(*country) != home_country)
+                    .map(|country| postal_country_name(country).to_string()),
+            )
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+/// Join the non-empty parts with a separator.
+fn join(parts: &[&str], separator: &str) -> String {
+    parts
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(country: &str) -> Address {
+        Address {
+            street: vec!["1 Main Street".to_string()],
+            city: Some("Springfield".to_string()),
+            region: Some("Region".to_string()),
+            postcode: Some("1234".to_string()),
+            country: Some(country.to_string()),
+        }
+    }
+
+    /// US addresses put the city, state, and ZIP code on one line.
+    #[test]
+    fn test_lines_us() {
+        assert_eq!(
+            address("US").lines(None),
+            vec!["1 Main Street", "Springfield, Region 1234", "United States"]
+        );
+    }
+
+    /// UK addresses put the postcode on its own line.
+    #[test]
+    fn test_lines_gb() {
+        assert_eq!(
+            address("GB").lines(None),
+            vec![
+                "1 Main Street",
+                "Springfield",
+                "Region",
+                "1234",
+                "United Kingdom"
+            ]
+        );
+    }
+
+    /// Other addresses put the postcode before the city.
+    #[test]
+    fn test_lines_default() {
+        assert_eq!(
+            address("DE").lines(None),
+            vec!["1 Main Street", "1234 Springfield", "Region", "Germany"]
+        );
+    }
+
+    /// The home country is left out.
+    #[test]
+    fn test_lines_home_country() {
+        assert_eq!(
+            address("DE").lines(Some("DE")),
+            vec!["1 Main Street", "1234 Springfield", "Region"]
+        );
+    }
+
+    /// Missing parts are left out.
+    #[test]
+    fn test_lines_partial() {
+        let address = Address {
+            street: vec![],
+            city: Some("Sydney".to_string()),
+            region: None,
+            postcode: Some("2000".to_string()),
+            country: Some("AU".to_string()),
+        };
+        assert_eq!(address.lines(None), vec!["Sydney 2000", "Australia"]);
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The table of country names, by ISO 3166-1 alpha-2 code.
+const COUNTRIES: &[(&str, &str)] = &[
+    ("AE", "the United Arab Emirates"),
+    ("AR", "Argentina"),
+    ("AT", "Austria"),
+    ("AU", "Australia"),
+    ("BE", "Belgium"),
+    ("BG", "Bulgaria"),
+    ("BR", "Brazil"),
+    ("CA", "Canada"),
+    ("CH", "Switzerland"),
+    ("CL", "Chile"),
+    ("CN", "China"),
+    ("CO", "Colombia"),
+    ("CY", "Cyprus"),
+    ("CZ", "the Czech Republic"),
+    ("DE", "Germany"),
+    ("DK", "Denmark"),
+    ("EE", "Estonia"),
+    ("EG", "Egypt"),
+    ("ES", "Spain"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("GB", "the United Kingdom"),
+    ("GR", "Greece"),
+    ("HK", "Hong Kong"),
+    ("HR", "Croatia"),
+    ("HU", "Hungary"),
+    ("ID", "Indonesia"),
+    ("IE", "Ireland"),
+    ("IL", "Israel"),
+    ("IN", "India"),
+    ("IS", "Iceland"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("KR", "South Korea"),
+    ("LT", "Lithuania"),
+    ("LU", "Luxembourg"),
+    ("LV", "Latvia"),
+    ("MT", "Malta"),
+    ("MX", "Mexico"),
+    ("MY", "Malaysia"),
+    ("NG", "Nigeria"),
+    ("NL", "the Netherlands"),
+    ("NO", "Norway"),
+    ("NZ", "New Zealand"),
+    ("PH", "the Philippines"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("RO", "Romania"),
+    ("SA", "Saudi Arabia"),
+    ("SE", "Sweden"),
+    ("SG", "Singapore"),
+    ("SI", "Slovenia"),
+    ("SK", "Slovakia"),
+    ("TH", "Thailand"),
+    ("TR", "Turkey"),
+    ("TW", "Taiwan"),
+    ("UA", "Ukraine"),
+    ("US", "the United States"),
+    ("VN", "Vietnam"),
+    ("ZA", "South Africa"),
+];
+
+/// The name of a country, as used in a sentence, given its ISO 3166-1
+/// alpha-2 code. Unknown codes are returned as they are.
+pub fn country_name(code: &str) -> &str {
+    COUNTRIES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+        .unwrap_or(code)
+}
+
+/// The name of a country as written on the last line of a postal address,
+/// e.g. `United States`. Unknown codes are returned as they are.
+pub fn postal_country_name(code: &str) -> &str {
+    let name: &str = country_name(code);
+    name.strip_prefix("the ").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Known codes are looked up in the table.
+    #[test]
+    fn test_country_name_known() {
+        assert_eq!(country_name("US"), "the United States");
+        assert_eq!(country_name("DE"), "Germany");
+    }
+
+    /// Postal names leave out the article.
+    #[test]
+    fn test_postal_country_name() {
+        assert_eq!(postal_country_name("US"), "United States");
+        assert_eq!(postal_country_name("FR"), "France");
+        assert_eq!(postal_country_name("XX"), "XX");
+    }
+
+    /// Unknown codes are returned unchanged.
+    #[test]
+    fn test_country_name_unknown() {
+        assert_eq!(country_name("XX"), "XX");
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::de::Error;
+
+/// An ISO 4217 currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    /// The three-letter currency code.
+    code: &'static str,
+    /// The number of digits after the decimal point.
+    minor_units: u32,
+    /// How amounts in this currency are displayed.
+    display: CurrencyDisplay,
+}
+
+/// How to display a currency next to an amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CurrencyDisplay {
+    /// Show the currency code after the amount, e.g. `100.00 AUD`. Used when
+    /// the symbol is ambiguous, like the dollar sign.
+    Code,
+    /// Show a symbol before the amount, e.g. `€100.00`.
+    Prefix(&'static str),
+    /// Show a symbol after the amount, e.g. `100.00 zł`.
+    Suffix(&'static str),
+}
+
+use CurrencyDisplay::Code;
+use CurrencyDisplay::Prefix;
+use CurrencyDisplay::Suffix;
+
+const fn currency(code: &'static str, minor_units: u32, display: CurrencyDisplay) -> Currency {
+    Currency {
+        code,
+        minor_units,
+        display,
+    }
+}
+
+/// The table of active ISO 4217 currencies, except those without minor
+/// units, like gold.
+const CURRENCIES: &[Currency] = &[
+    currency("AED", 2, Code),
+    currency("AFN", 2, Code),
+    currency("ALL", 2, Code),
+    currency("AMD", 2, Code),
+    currency("AOA", 2, Code),
+    currency("ARS", 2, Code),
+    currency("AUD", 2, Code),
+    currency("AWG", 2, Code),
+    currency("AZN", 2, Code),
+    currency("BAM", 2, Code),
+    currency("BBD", 2, Code),
+    currency("BDT", 2, Code),
+    currency("BGN", 2, Code),
+    currency("BHD", 3, Code),
+    currency("BIF", 0, Code),
+    currency("BMD", 2, Code),
+    currency("BND", 2, Code),
+    currency("BOB", 2, Code),
+    currency("BOV", 2, Code),
+    currency("BRL", 2, Prefix("R$")),
+    currency("BSD", 2, Code),
+    currency("BTN", 2, Code),
+    currency("BWP", 2, Code),
+    currency("BYN", 2, Code),
+    currency("BZD", 2, Code),
+    currency("CAD", 2, Code),
+    currency("CDF", 2, Code),
+    currency("CHE", 2, Code),
+    currency("CHF", 2, Code),
+    currency("CHW", 2, Code),
+    currency("CLF", 4, Code),
+    currency("CLP", 0, Code),
+    currency("CNY", 2, Code),
+    currency("COP", 2, Code),
+    currency("COU", 2, Code),
+    currency("CRC", 2, Code),
+    currency("CUC", 2, Code),
+    currency("CUP", 2, Code),
+    currency("CVE", 2, Code),
+    currency("CZK", 2, Suffix("Kč")),
+    currency("DJF", 0, Code),
+    currency("DKK", 2, Code),
+    currency("DOP", 2, Code),
+    currency("DZD", 2, Code),
+    currency("EGP", 2, Code),
+    currency("ERN", 2, Code),
+    currency("ETB", 2, Code),
+    currency("EUR", 2, Prefix("€")),
+    currency("FJD", 2, Code),
+    currency("FKP", 2, Code),
+    currency("GBP", 2, Prefix("£")),
+    currency("GEL", 2, Code),
+    currency("GHS", 2, Code),
+    currency("GIP", 2, Code),
+    currency("GMD", 2, Code),
+    currency("GNF", 0, Code),
+    currency("GTQ", 2, Code),
+    currency("GYD", 2, Code),
+    currency("HKD", 2, Code),
+    currency("HNL", 2, Code),
+    currency("HTG", 2, Code),
+    currency("HUF", 2, Suffix("Ft")),
+    currency("IDR", 2, Code),
+    currency("ILS", 2, Prefix("₪")),
+    currency("INR", 2, Prefix("₹")),
+    currency("IQD", 3, Code),
+    currency("IRR", 2, Code),
+    currency("ISK", 0, Code),
+    currency("JMD", 2, Code),
+    currency("JOD", 3, Code),
+    currency("JPY", 0, Prefix("¥")),
+    currency("KES", 2, Code),
+    currency("KGS", 2, Code),
+    currency("KHR", 2, Code),
+    currency("KMF", 0, Code),
+    currency("KPW", 2, Code),
+    currency("KRW", 0, Prefix("₩")),
+    currency("KWD", 3, Code),
+    currency("KYD", 2, Code),
+    currency("KZT", 2, Code),
+    currency("LAK", 2, Code),
+    currency("LBP", 2, Code),
+    currency("LKR", 2, Code),
+    currency("LRD", 2, Code),
+    currency("LSL", 2, Code),
+    currency("LYD", 3, Code),
+    currency("MAD", 2, Code),
+    currency("MDL", 2, Code),
+    currency("MGA", 2, Code),
+    currency("MKD", 2, Code),
+    currency("MMK", 2, Code),
+    currency("MNT", 2, Code),
+    currency("MOP", 2, Code),
+    currency("MRU", 2, Code),
+    currency("MUR", 2, Code),
+    currency("MVR", 2, Code),
+    currency("MWK", 2, Code),
+    currency("MXN", 2, Code),
+    currency("MXV", 2, Code),
+    currency("MYR", 2, Code),
+    currency("MZN", 2, Code),
+    currency("NAD", 2, Code),
+    currency("NGN", 2, Prefix("₦")),
+    currency("NIO", 2, Code),
+    currency("NOK", 2, Code),
+    currency("NPR", 2, Code),
+    currency("NZD", 2, Code),
+    currency("OMR", 3, Code),
+    currency("PAB", 2, Code),
+    currency("PEN", 2, Code),
+    currency("PGK", 2, Code),
+    currency("PHP", 2, Prefix("₱")),
+    currency("PKR", 2, Code),
+    currency("PLN", 2, Suffix("zł")),
+    currency("PYG", 0, Code),
+    currency("QAR", 2, Code),
+    currency("RON", 2, Code),
+    currency("RSD", 2, Code),
+    currency("RUB", 2, Code),
+    currency("RWF", 0, Code),
+    currency("SAR", 2, Code),
+    currency("SBD", 2, Code),
+    currency("SCR", 2, Code),
+    currency("SDG", 2, Code),
+    currency("SEK", 2, Code),
+    currency("SGD", 2, Code),
+    currency("SHP", 2, Code),
+    currency("SLE", 2, Code),
+    currency("SOS", 2, Code),
+    currency("SRD", 2, Code),
+    currency("SSP", 2, Code),
+    currency("STN", 2, Code),
+    currency("SVC", 2, Code),
+    currency("SYP", 2, Code),
+    currency("SZL", 2, Code),
+    currency("THB", 2, Prefix("฿")),
+    currency("TJS", 2, Code),
+    currency("TMT", 2, Code),
+    currency("TND", 3, Code),
+    currency("TOP", 2, Code),
+    currency("TRY", 2, Prefix("₺")),
+    currency("TTD", 2, Code),
+    currency("TWD", 2, Code),
+    currency("TZS", 2, Code),
+    currency("UAH", 2, Prefix("₴")),
+    currency("UGX", 0, Code),
+    currency("USD", 2, Code),
+    currency("USN", 2, Code),
+    currency("UYI", 0, Code),
+    currency("UYU", 2, Code),
+    currency("UYW", 4, Code),
+    currency("UZS", 2, Code),
+    currency("VED", 2, Code),
+    currency("VES", 2, Code),
+    currency("VND", 0, Suffix("₫")),
+    currency("VUV", 0, Code),
+    currency("WST", 2, Code),
+    currency("XAF", 0, Code),
+    currency("XCD", 2, Code),
+    currency("XCG", 2, Code),
+    currency("XOF", 0, Code),
+    currency("XPF", 0, Code),
+    currency("YER", 2, Code),
+    currency("ZAR", 2, Code),
+    currency("ZMW", 2, Code),
+    currency("ZWG", 2, Code),
+];
+
+impl Currency {
+    /// Look up a currency by its ISO 4217 code.
+    pub fn from_code(code: &str) -> Option<Currency> {
+        CURRENCIES.iter().find(|c| c.code == code).copied()
+    }
+
+    /// The three-letter currency code.
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Round an amount to the currency's minor units.
+    pub fn round(&self, amount: Decimal, strategy: RoundingStrategy) -> Decimal {
+        amount.round_dp_with_strategy(self.minor_units, strategy)
+    }
+
+    /// Format an amount in this currency, rounded to the currency's minor
+    /// units.
+    pub fn format(&self, amount: Decimal) -> String {
+        let rounded: Decimal = self.round(amount, RoundingStrategy::MidpointAwayFromZero);
+        let digits: String = format!("{:.*}", self.minor_units as usize, rounded.abs());
+        let sign: &str = if rounded.is_sign_negative() && !rounded.is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        match self.display {
+            Code => format!("{sign}{digits} {}", self.code),
+            Prefix(symbol) => format!("{sign}{symbol}{digits}"),
+            Suffix(symbol) => format!("{sign}{digits} {symbol}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code: String = String::deserialize(deserializer)?;
+        Currency::from_code(&code)
+            .ok_or_else(|| D::Error::custom(format!("unknown currency code: {code}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    /// Known currency codes are found in the table.
+    #[test]
+    fn test_from_code_known() {
+        let currency = Currency::from_code("AUD").unwrap();
+        assert_eq!(currency.code(), "AUD");
+        assert_eq!(currency.minor_units, 2);
+    }
+
+    /// Codes from the whole ISO 4217 list are found, not only the most
+    /// common ones.
+    #[test]
+    fn test_from_code_full_list() {
+        assert_eq!(Currency::from_code("QAR").unwrap().minor_units, 2);
+        assert_eq!(Currency::from_code("XOF").unwrap().minor_units, 0);
+        assert_eq!(Currency::from_code("CLF").unwrap().minor_units, 4);
+    }
+
+    /// Unknown currency codes are rejected.
+    #[test]
+    fn test_from_code_unknown() {
+        assert!(Currency::from_code("XYZ").is_none());
+        assert!(Currency::from_code("aud").is_none());
+    }
+
+    /// Currencies displayed by code put the code after the amount.
+    #[test]
+    fn test_format_code() {
+        let currency = Currency::from_code("AUD").unwrap();
+        assert_eq!(currency.format(dec!(1234.5)), "1234.50 AUD");
+    }
+
+    /// Currencies with no minor units are formatted without decimals.
+    #[test]
+    fn test_format_no_minor_units() {
+        let currency = Currency::from_code("JPY").unwrap();
+        assert_eq!(currency.format(dec!(15000)), "¥15000");
+        assert_eq!(currency.format(dec!(15000.5)), "¥15001");
+    }
+
+    /// Currencies with three minor units keep the third decimal place.
+    #[test]
+    fn test_format_three_minor_units() {
+        let currency = Currency::from_code("KWD").unwrap();
+        assert_eq!(currency.format(dec!(12.345)), "12.345 KWD");
+    }
+
+    /// Suffix symbols go after the amount.
+    #[test]
+    fn test_format_suffix() {
+        let currency = Currency::from_code("PLN").unwrap();
+        assert_eq!(currency.format(dec!(99.9)), "99.90 zł");
+    }
+
+    /// The sign goes before a prefix symbol.
+    #[test]
+    fn test_format_negative() {
+        let currency = Currency::from_code("EUR").unwrap();
+        assert_eq!(currency.format(dec!(-5)), "-€5.00");
+    }
+}
@@ -12,18 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::NaiveDate;
 use maud::Markup;
 use maud::PreEscaped;
+use maud::Render;
 use maud::html;
+use rust_decimal::Decimal;
 
-use crate::invoice::Expense;
+use crate::address::Address;
+use crate::country::postal_country_name;
+use crate::invoice::Discount;
 use crate::invoice::Invoice;
-use crate::invoice::Labour;
+use crate::invoice::LineItem;
+use crate::invoice::PaymentMethod;
+use crate::invoice::ReportingAmounts;
+use crate::invoice::Tax;
+use crate::invoice::TaxSummary;
+use crate::invoice::TaxTotal;
+use crate::markdown::render_markdown;
 
 const STYLESHEET: &str = include_str!("style.css");
 
-/// Render an invoice to HTML.
-pub fn render_html(invoice: &Invoice) -> Markup {
+/// Render an invoice to HTML. If `as_of` is given, late charges accrued by
+/// that date are added to the balance due.
+pub fn render_html(invoice: &Invoice, as_of: Option<NaiveDate>) -> Markup {
+    let taxes: Vec<Tax> = invoice.taxes();
+    let tax_summary: Vec<TaxSummary> = invoice.tax_summary();
+    // Itemize taxes when there's more than one, otherwise show the single
+    // tax's rate in the totals.
+    let itemize_taxes: bool = taxes.len() > 1 || tax_summary.iter().any(|line| line.code.is_some());
+    let tax_name: &str = if itemize_taxes { "Tax" } else { &taxes[0].name };
+    let payment_methods: Vec<PaymentMethod> = invoice.payment_methods();
+    let home_country: Option<&str> = invoice.home_country();
+    let recipient_country: Option<&str> = invoice
+        .recipient
+        .country
+        .as_deref()
+        .filter(|country| Some(*country) != home_country);
+    let document: &str = if invoice.is_credit_note() {
+        "credit note"
+    } else {
+        "invoice"
+    };
     html! {
         (PreEscaped("<!doctype html>"))
         html lang="en" {
@@ -39,13 +69,21 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                 div class="page" {
                     // Invoice metadata section
                     div class="section" {
-                        div class="big-title" { "invoice" }
+                        div class="big-title" { (document) }
                         div class="content" {
                             table class="kv-table" {
                                 tr {
-                                    td class="key" { "invoice #" }
+                                    td class="key" { (document) " #" }
                                     td class="val" { (invoice.metadata.invoice_id) }
                                 }
+                                @if let Some(credited) = &invoice.metadata.credited_invoice {
+                                    tr {
+                                        td class="key" { "credited to invoice #" }
+                                        td class="val" {
+                                            (credited.invoice_id) " of " (credited.issue_date)
+                                        }
+                                    }
+                                }
                                 tr {
                                     td class="key" { "issue date" }
                                     td class="val" { (invoice.metadata.issue_date) }
@@ -54,6 +92,15 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                                     td class="key" { "payment terms" }
                                     td class="val" { (invoice.metadata.payment_terms) }
                                 }
+                                @if let Some(due_date) = invoice.due_date() {
+                                    tr {
+                                        td class="key" { "due date" }
+                                        td class="val" { (due_date) }
+                                    }
+                                }
+                                @for field in &invoice.metadata.custom {
+                                    (render_kv_row(&field.label, &field.value))
+                                }
                             }
                         }
                     }
@@ -64,12 +111,17 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                         div class="content" {
                             div class="contact" {
                                 div class="line" { (invoice.issuer.name) }
+                                @if let Some(address) = &invoice.issuer.address {
+                                    (render_address(address, home_country))
+                                }
                                 div class="line" {
                                     a href=(format!("mailto:{}", invoice.issuer.email)) {
                                         (invoice.issuer.email)
                                     }
                                 }
-                                div class="line" { "ABN " (invoice.issuer.abn) }
+                                @if let Some(registration) = invoice.issuer.tax_registration() {
+                                    div class="line" { (registration.scheme.label()) " " (registration.number) }
+                                }
                             }
                         }
                     }
@@ -80,12 +132,34 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                         div class="content" {
                             div class="contact" {
                                 div class="line" { (invoice.recipient.name) }
-                                div class="line" { (invoice.recipient.company) }
+                                @if let Some(company) = &invoice.recipient.company {
+                                    div class="line" { (company) }
+                                }
+                                @if let Some(attention) = &invoice.recipient.attention {
+                                    div class="line" { "Attn: " (attention) }
+                                }
+                                @if let Some(address) = &invoice.recipient.address {
+                                    (render_address(address, home_country))
+                                } @else if let Some(country) = recipient_country {
+                                    div class="line" { (postal_country_name(country)) }
+                                }
                                 div class="line" {
                                     a href=(format!("mailto:{}", invoice.recipient.email)) {
                                         (invoice.recipient.email)
                                     }
                                 }
+                                @for email in &invoice.recipient.cc {
+                                    div class="line" {
+                                        "CC: "
+                                        a href=(format!("mailto:{email}")) { (email) }
+                                    }
+                                }
+                                @if let Some(tax_id) = &invoice.recipient.tax_id {
+                                    div class="line" { "Tax ID " (tax_id) }
+                                }
+                                @if let Some(reference) = &invoice.recipient.reference {
+                                    div class="line" { "Reference " (reference) }
+                                }
                             }
                         }
                     }
@@ -105,23 +179,31 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                                     }
                                 }
                                 tbody {
-                                    // Labour items
-                                    @if !invoice.labour.is_empty() {
-                                        tr class="table-heading" {
-                                            th colspan="5" { "Labour" }
-                                        }
-                                        @for item in &invoice.labour {
-                                            (render_labour_row(item, &invoice.metadata.currency))
+                                    // Line items, by section
+                                    @for (heading, items) in invoice.sections() {
+                                        @if !items.is_empty() {
+                                            tr class="table-heading" {
+                                                th colspan="5" { (heading) }
+                                            }
+                                            @for item in items {
+                                                (render_item_row(invoice, item))
+                                            }
                                         }
                                     }
 
-                                    // Expense items
-                                    @if !invoice.expenses.is_empty() {
+                                    // Tax summary
+                                    @if itemize_taxes {
                                         tr class="table-heading" {
-                                            th colspan="5" { "Expenses" }
+                                            th colspan="5" { "Tax Summary" }
+                                        }
+                                        tr class="column-headings" {
+                                            td colspan="2" { "Tax" }
+                                            td class="numeric-cell" { "Taxable" }
+                                            td class="numeric-cell" { "Rate" }
+                                            td class="numeric-cell" { "Tax" }
                                         }
-                                        @for item in &invoice.expenses {
-                                            (render_expense_row(item, &invoice.metadata.currency))
+                                        @for line in &tax_summary {
+                                            (render_tax_summary_row(invoice, line))
                                         }
                                     }
 
@@ -132,90 +214,605 @@ pub fn render_html(invoice: &Invoice) -> Markup {
                                     tr {
                                         td class="total" colspan="4" { "Subtotal" }
                                         td class="numeric-cell" {
-                                            (format_currency(&invoice.metadata.currency, invoice.subtotal()))
+                                            (invoice.metadata.currency.format(invoice.subtotal()))
                                         }
                                     }
-                                    tr {
-                                        td class="total" colspan="4" { "Tax Rate" }
-                                        td class="numeric-cell" {
-                                            (format!("{}%", invoice.metadata.tax_rate))
+                                    @for (discount, amount) in invoice.discounts.iter().zip(invoice.discount_amounts()) {
+                                        tr {
+                                            td class="total" colspan="4" {
+                                                (discount_label(&discount.description, &discount.discount))
+                                            }
+                                            td class="numeric-cell" {
+                                                (invoice.metadata.currency.format(-amount))
+                                            }
                                         }
                                     }
-                                    tr {
-                                        td class="total" colspan="4" { "Total Tax" }
-                                        td class="numeric-cell" {
-                                            (format_currency(&invoice.metadata.currency, invoice.tax_amount()))
+                                    @if !itemize_taxes {
+                                        tr {
+                                            td class="total" colspan="4" { "Tax Rate" }
+                                            td class="numeric-cell" {
+                                                (format_rate(taxes[0].rate))
+                                            }
+                                        }
+                                    }
+                                    @if itemize_taxes {
+                                        @for total in invoice.tax_totals() {
+                                            (render_tax_total_row(invoice, &total))
+                                        }
+                                    }
+                                    @if invoice.metadata.prices_include_tax {
+                                        tr {
+                                            td class="total" colspan="4" { "Includes " (tax_name) " of" }
+                                            td class="numeric-cell" {
+                                                (invoice.metadata.currency.format(invoice.tax_amount()))
+                                            }
+                                        }
+                                    } @else {
+                                        tr {
+                                            td class="total" colspan="4" { "Total " (tax_name) }
+                                            td class="numeric-cell" {
+                                                (invoice.metadata.currency.format(invoice.tax_amount()))
+                                            }
+                                        }
+                                    }
+                                    @if invoice.withholding.is_some() || !invoice.payments_received.is_empty() {
+                                        tr {
+                                            td class="total" colspan="4" { "Total" }
+                                            td class="numeric-cell" {
+                                                (invoice.metadata.currency.format(invoice.total()))
+                                            }
+                                        }
+                                    }
+                                    @if let Some(withholding) = &invoice.withholding {
+                                        tr {
+                                            td class="total" colspan="4" {
+                                                "Less " (withholding.name) " (" (format_rate(withholding.rate)) ")"
+                                            }
+                                            td class="numeric-cell" {
+                                                (invoice.metadata.currency.format(-invoice.withholding_amount()))
+                                            }
+                                        }
+                                    }
+                                    @for payment in &invoice.payments_received {
+                                        tr {
+                                            td class="total" colspan="4" {
+                                                @if invoice.is_credit_note() {
+                                                    "Refunded " (payment.date)
+                                                } @else {
+                                                    "Less: paid " (payment.date)
+                                                }
+                                                @if let Some(reference) = &payment.reference {
+                                                    " (" (reference) ")"
+                                                }
+                                            }
+                                            td class="numeric-cell" {
+                                                @if invoice.is_credit_note() {
+                                                    (invoice.metadata.currency.format(payment.amount))
+                                                } @else {
+                                                    (invoice.metadata.currency.format(-payment.amount))
+                                                }
+                                            }
                                         }
                                     }
                                     tr {
-                                        td class="total" colspan="4" { "Balance Due" }
+                                        td class="total" colspan="4" {
+                                            @if invoice.is_credit_note() { "Total Credit" } @else { "Balance Due" }
+                                        }
                                         td class="numeric-cell" {
-                                            (format_currency(&invoice.metadata.currency, invoice.total()))
+                                            (invoice.metadata.currency.format(invoice.balance_due()))
                                         }
                                     }
+                                    @if let Some(as_of) = as_of {
+                                        (render_late_charge_rows(invoice, as_of))
+                                    }
+
+                                    // Amounts in the reporting currency
+                                    @if let Some(reporting) = invoice.reporting_amounts() {
+                                        (render_reporting_rows(invoice, &reporting))
+                                    }
                                 }
                             }
                         }
                     }
 
+                    // Tax notice section
+                    @if let Some(notice) = invoice.tax_notice() {
+                        div class="section" {
+                            div class="title" { "tax" }
+                            div class="content" { (notice) }
+                        }
+                    }
+
                     // Payment section
                     div class="section" {
                         div class="title" { "payment" }
                         div class="content" {
-                            table class="kv-table" {
-                                tr {
-                                    td class="key" { "name" }
-                                    td class="val" { (invoice.payment.name) }
-                                }
-                                tr {
-                                    td class="key" { "bsb" }
-                                    td class="val" { (invoice.payment.bsb) }
-                                }
-                                tr {
-                                    td class="key" { "acct" }
-                                    td class="val" { (invoice.payment.acct) }
-                                }
-                                tr {
-                                    td class="key" { "bank" }
-                                    td class="val" { (invoice.payment.bank) }
-                                }
-                                tr {
-                                    td class="key" { "bic/swift" }
-                                    td class="val" { (invoice.payment.swift) }
+                            @for method in &payment_methods {
+                                @if payment_methods.len() > 1 {
+                                    div class="subtitle" { (payment_method_title(method)) }
                                 }
+                                (render_payment_method(method))
                             }
                         }
                     }
+
+                    // Notes section
+                    @if let Some(notes) = &invoice.notes {
+                        div class="section" {
+                            div class="title" { "notes" }
+                            div class="content markdown" { (render_markdown(notes)) }
+                        }
+                    }
+
+                    // Terms section
+                    @if let Some(terms) = &invoice.terms {
+                        div class="section" {
+                            div class="title" { "terms" }
+                            div class="content markdown" { (render_markdown(terms)) }
+                        }
+                    }
+
+                    // Footer
+                    @if let Some(footer) = &invoice.footer {
+                        div class="footer markdown" { (render_markdown(footer)) }
+                    }
                 }
             }
         }
     }
 }
 
-fn render_labour_row(item: &Labour, currency: &str) -> Markup {
+fn render_payment_method(method: &PaymentMethod) -> Markup {
+    html! {
+        table class="kv-table" {
+            @match method {
+                PaymentMethod::AuBank { name, bsb, acct, bank, swift } => {
+                    (render_kv_row("name", name))
+                    (render_kv_row("bsb", bsb))
+                    (render_kv_row("acct", acct))
+                    (render_kv_row("bank", bank))
+                    @if let Some(swift) = swift {
+                        (render_kv_row("bic/swift", swift))
+                    }
+                }
+                PaymentMethod::Iban { name, iban, bic, bank } => {
+                    (render_kv_row("name", name))
+                    (render_kv_row("iban", iban))
+                    @if let Some(bic) = bic {
+                        (render_kv_row("bic/swift", bic))
+                    }
+                    @if let Some(bank) = bank {
+                        (render_kv_row("bank", bank))
+                    }
+                }
+                PaymentMethod::Ach { name, routing_number, account_number, bank } => {
+                    (render_kv_row("name", name))
+                    (render_kv_row("routing #", routing_number))
+                    (render_kv_row("account #", account_number))
+                    @if let Some(bank) = bank {
+                        (render_kv_row("bank", bank))
+                    }
+                }
+                PaymentMethod::UkBank { name, sort_code, account_number, bank } => {
+                    (render_kv_row("name", name))
+                    (render_kv_row("sort code", sort_code))
+                    (render_kv_row("account #", account_number))
+                    @if let Some(bank) = bank {
+                        (render_kv_row("bank", bank))
+                    }
+                }
+                PaymentMethod::PayId { name, pay_id } => {
+                    (render_kv_row("name", name))
+                    (render_kv_row("payid", pay_id))
+                }
+                PaymentMethod::Link { url, label } => {
+                    (render_kv_row("pay online", html! {
+                        a href=(url) { (label.as_deref().unwrap_or(url)) }
+                    }))
+                }
+            }
+        }
+    }
+}
+
+fn render_kv_row(key: &str, value: impl Render) -> Markup {
     html! {
         tr {
-            td class="date-cell" { (item.date) }
-            td { (item.description) }
-            td class="numeric-cell" { (item.quantity) }
-            td class="numeric-cell" { (format_currency(currency, item.unit_price)) }
-            td class="numeric-cell" { (format_currency(currency, item.total())) }
+            td class="key" { (key) }
+            td class="val" { (value) }
         }
     }
 }
 
-fn render_expense_row(item: &Expense, currency: &str) -> Markup {
+fn payment_method_title(method: &PaymentMethod) -> &'static str {
+    match method {
+        PaymentMethod::AuBank { .. } => "Bank transfer (Australia)",
+        PaymentMethod::Iban { .. } => "Bank transfer (IBAN)",
+        PaymentMethod::Ach { .. } => "ACH transfer (US)",
+        PaymentMethod::UkBank { .. } => "Bank transfer (UK)",
+        PaymentMethod::PayId { .. } => "PayID",
+        PaymentMethod::Link { .. } => "Online",
+    }
+}
+
+/// Render a line item, with its markup and discount. On a credit note, the
+/// amounts are negative, like the totals.
+fn render_item_row(invoice: &Invoice, item: &LineItem) -> Markup {
+    let sign: Decimal = invoice.sign();
     html! {
         tr {
             td class="date-cell" { (item.date) }
-            td { (item.description) }
-            td class="numeric-cell" { (item.quantity) }
-            td class="numeric-cell" { (format_currency(currency, item.unit_price)) }
-            td class="numeric-cell" { (format_currency(currency, item.total())) }
+            td {
+                @if let Some(sku) = &item.sku {
+                    span class="sku" { (sku) }
+                    " "
+                }
+                (item.description)
+                @if item.reimbursable {
+                    " (reimbursable)"
+                }
+                @if let Some(details) = &item.details {
+                    div class="item-details markdown" { (render_markdown(details)) }
+                }
+            }
+            td class="numeric-cell" { (format_quantity(item.quantity, item.unit.as_deref())) }
+            td class="numeric-cell" { (invoice.metadata.currency.format(sign * item.unit_price)) }
+            td class="numeric-cell" { (invoice.metadata.currency.format(sign * invoice.round(item.total()))) }
+        }
+        @if let Some(markup) = item.markup {
+            tr class="markup-row" {
+                td {}
+                td colspan="3" { "Markup (" (format_rate(markup)) ")" }
+                td class="numeric-cell" { (invoice.metadata.currency.format(sign * invoice.markup_amount(item))) }
+            }
+        }
+        @if let Some(discount) = &item.discount {
+            (render_line_discount_row(invoice, discount, sign * invoice.line_discount(item)))
+        }
+    }
+}
+
+fn render_address(address: &Address, home_country: Option<&str>) -> Markup {
+    html! {
+        @for line in address.lines(home_country) {
+            div class="line" { (line) }
+        }
+    }
+}
+
+fn render_line_discount_row(invoice: &Invoice, discount: &Discount, amount: Decimal) -> Markup {
+    html! {
+        tr class="discount-row" {
+            td {}
+            td colspan="3" { (discount_label("Discount", discount)) }
+            td class="numeric-cell" { (invoice.metadata.currency.format(-amount)) }
+        }
+    }
+}
+
+fn render_late_charge_rows(invoice: &Invoice, as_of: NaiveDate) -> Markup {
+    let fee: Decimal = invoice.late_fee(as_of);
+    let interest: Decimal = invoice.late_interest(as_of);
+    let rate: Option<Decimal> = invoice
+        .late_fee
+        .as_ref()
+        .and_then(|policy| policy.annual_rate);
+    html! {
+        @if !fee.is_zero() || !interest.is_zero() {
+            @if !fee.is_zero() {
+                tr {
+                    td class="total" colspan="4" { "Late Fee" }
+                    td class="numeric-cell" { (invoice.metadata.currency.format(fee)) }
+                }
+            }
+            @if let Some(rate) = rate && !interest.is_zero() {
+                tr {
+                    td class="total" colspan="4" {
+                        "Interest (" (invoice.days_overdue(as_of)) " days at " (format_rate(rate)) " p.a.)"
+                    }
+                    td class="numeric-cell" { (invoice.metadata.currency.format(interest)) }
+                }
+            }
+            tr {
+                td class="total" colspan="4" { "Balance Due as of " (as_of) }
+                td class="numeric-cell" {
+                    (invoice.metadata.currency.format(invoice.balance_due_as_of(as_of)))
+                }
+            }
+        }
+    }
+}
+
+fn render_reporting_rows(invoice: &Invoice, reporting: &ReportingAmounts) -> Markup {
+    let from: &str = invoice.metadata.currency.code();
+    let to: &str = reporting.currency.code();
+    html! {
+        tr class="table-heading" {
+            th colspan="5" {
+                "In " (to) " (1 " (from) " = " (reporting.exchange_rate.normalize()) " " (to) ")"
+            }
+        }
+        tr {
+            td class="total" colspan="4" { "Total excl. Tax" }
+            td class="numeric-cell" { (reporting.currency.format(reporting.net)) }
+        }
+        tr {
+            td class="total" colspan="4" { "Total Tax" }
+            td class="numeric-cell" { (reporting.currency.format(reporting.tax)) }
+        }
+        tr {
+            td class="total" colspan="4" { "Total" }
+            td class="numeric-cell" { (reporting.currency.format(reporting.total)) }
+        }
+    }
+}
+
+fn render_tax_summary_row(invoice: &Invoice, line: &TaxSummary) -> Markup {
+    let label: String = match &line.code {
+        Some(code) => match &invoice.tax_codes[code].description {
+            Some(description) => format!("{code} ({description})"),
+            None => code.clone(),
+        },
+        None => line.name.clone(),
+    };
+    html! {
+        tr {
+            td colspan="2" { (label) }
+            td class="numeric-cell" { (invoice.metadata.currency.format(line.base)) }
+            td class="numeric-cell" { (format_rate(line.rate)) }
+            td class="numeric-cell" { (invoice.metadata.currency.format(line.tax)) }
+        }
+    }
+}
+
+fn render_tax_total_row(invoice: &Invoice, total: &TaxTotal) -> Markup {
+    html! {
+        tr {
+            td class="total" colspan="4" { (total.name) " (" (format_rate(total.rate)) ")" }
+            td class="numeric-cell" { (invoice.metadata.currency.format(total.tax)) }
         }
     }
 }
 
-fn format_currency(currency: &str, amount: f64) -> String {
-    format!("{:.2} {}", amount, currency)
+fn discount_label(description: &str, discount: &Discount) -> String {
+    match discount {
+        Discount::Percent(percent) => format!("{description} ({})", format_rate(*percent)),
+        Discount::Amount(_) => description.to_string(),
+    }
+}
+
+fn format_quantity(quantity: Decimal, unit: Option<&str>) -> String {
+    match unit {
+        Some(unit) => format!("{} {unit}", quantity.normalize()),
+        None => quantity.normalize().to_string(),
+    }
+}
+
+fn format_rate(rate: Decimal) -> String {
+    format!("{}%", rate.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Fallible;
+
+    /// A row in the totals section.
+    fn total_row(label: &str, value: &str) -> String {
+        format!(
+            r#"<td class="total" colspan="4">{label}</td><td class="numeric-cell">{value}</td>"#
+        )
+    }
+
+    /// An export invoice shows a zero tax rate in the totals.
+    #[test]
+    fn test_render_export_totals() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name       = "Wintermute"
+            company    = "Tessier-Ashpool S.A."
+            email      = "wmute@ta.sa"
+            country    = "US"
+            tax_status = "export"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let html: String = render_html(&invoice, None).into_string();
+        assert!(html.contains(&total_row("Tax Rate", "0%")));
+        assert!(html.contains(&total_row("Total Tax", "0.00 AUD")));
+        assert!(html.contains(&total_row("Balance Due", "1200.00 AUD")));
+        assert!(!html.contains("10%"));
+        assert!(
+            html.contains(
+                "GST-free export: services supplied to a recipient in the United States."
+            )
+        );
+        Ok(())
+    }
+
+    /// A credit note shows its line items as credits, so that they add up to
+    /// the subtotal.
+    #[test]
+    fn test_render_credit_note_signs() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            kind          = "credit-note"
+            invoice_id    = "1730"
+            issue_date    = "2052-07-15"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+            credited_invoice = { invoice_id = "1729", issue_date = "2052-06-30" }
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-01"
+            description = "Cancelled work"
+            unit_price  = 300.0
+            quantity    = 4
+            discount    = { percent = 10.0 }
+
+            [[discounts]]
+            description = "Goodwill"
+            amount      = 80.0
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let html: String = render_html(&invoice, None).into_string();
+        let cell = |value: &str| format!(r#"<td class="numeric-cell">{value}</td>"#);
+        assert!(html.contains(&cell("-300.00 AUD")));
+        assert!(html.contains(&cell("-1200.00 AUD")));
+        assert!(html.contains(&cell("120.00 AUD")));
+        assert!(html.contains(&total_row("Subtotal", "-1080.00 AUD")));
+        assert!(html.contains(&total_row("Goodwill", "80.00 AUD")));
+        assert!(html.contains(&total_row("Total Tax", "-100.00 AUD")));
+        assert!(html.contains(&total_row("Total Credit", "-1100.00 AUD")));
+        Ok(())
+    }
+
+    /// Addresses end with the country's name, which is left out when the
+    /// issuer and the recipient are in the same country.
+    #[test]
+    fn test_render_address_countries() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [issuer.address]
+            street   = ["1 George Street"]
+            city     = "Sydney"
+            region   = "NSW"
+            postcode = "2000"
+            country  = "AU"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [recipient.address]
+            street   = ["Villa Straylight"]
+            city     = "Freeside"
+            postcode = "75001"
+            country  = "FR"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let line = |text: &str| format!(r#"<div class="line">{text}</div>"#);
+        let invoice = Invoice::parse_string(text)?;
+        let html: String = render_html(&invoice, None).into_string();
+        assert!(html.contains(&line("Australia")));
+        assert!(html.contains(&line("France")));
+        assert!(!html.contains(&line("FR")));
+        let domestic = text.replace(r#"country  = "FR""#, r#"country  = "AU""#);
+        let invoice = Invoice::parse_string(&domestic)?;
+        let html: String = render_html(&invoice, None).into_string();
+        assert!(!html.contains(&line("Australia")));
+        Ok(())
+    }
+
+    /// Each tax gets its own row in the totals, before the total tax.
+    #[test]
+    fn test_render_tax_totals() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            currency      = "CAD"
+
+            [[metadata.taxes]]
+            name = "GST"
+            rate = 5.0
+
+            [[metadata.taxes]]
+            name = "PST"
+            rate = 7.0
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 250.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let html: String = render_html(&invoice, None).into_string();
+        let gst: String = total_row("GST (5%)", "50.00 CAD");
+        let pst: String = total_row("PST (7%)", "70.00 CAD");
+        let total: String = total_row("Total Tax", "120.00 CAD");
+        let position = |row: &str| html.find(row).unwrap();
+        assert!(position(&gst) < position(&pst));
+        assert!(position(&pst) < position(&total));
+        Ok(())
+    }
 }
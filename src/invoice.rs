@@ -12,34 +12,262 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::path::PathBuf;
 
 use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
 use serde::Deserialize;
 
+use crate::address::Address;
+use crate::country::country_name;
+use crate::currency::Currency;
 use crate::error::Fallible;
+use crate::error::ScriptError;
+use crate::rates::lookup_rate;
+use crate::terms::PaymentTerms;
 
-/// An invoice.
+/// An invoice. Its line items are priced with `Price`: an `Option<Decimal>`
+/// as parsed, when prices can come from the rate card, and a `Decimal` once
+/// resolved.
 #[derive(Debug, Deserialize)]
-pub struct Invoice {
+pub struct Invoice<Price = Decimal> {
     pub metadata: Metadata,
     pub issuer: Issuer,
     pub recipient: Recipient,
     #[serde(default)]
-    pub labour: Vec<Labour>,
+    pub labour: Vec<LineItem<Price>>,
     #[serde(default)]
-    pub expenses: Vec<Expense>,
-    pub payment: Payment,
+    pub expenses: Vec<LineItem<Price>>,
+    /// Further sections of line items, shown after labour and expenses in
+    /// the order given.
+    #[serde(default)]
+    pub sections: Vec<Section<Price>>,
+    /// An Australian bank account to pay into. Shorthand for an `au-bank`
+    /// payment method.
+    pub payment: Option<Payment>,
+    /// The ways the client can pay, in order.
+    #[serde(default)]
+    pub payment_methods: Vec<PaymentMethod>,
+    #[serde(default)]
+    pub tax_codes: BTreeMap<String, TaxCode>,
+    /// Named rates line items can refer to, in addition to those in the
+    /// rate card file.
+    #[serde(default)]
+    pub rate_card: BTreeMap<String, Rate>,
+    pub withholding: Option<Withholding>,
+    /// Discounts on the whole invoice, applied in order after line item
+    /// discounts and before tax.
+    #[serde(default)]
+    pub discounts: Vec<InvoiceDiscount>,
+    /// Deposits and partial payments already received.
+    #[serde(default)]
+    pub payments_received: Vec<PaymentReceived>,
+    pub late_fee: Option<LateFee>,
+    /// Notes for the client, in Markdown.
+    pub notes: Option<String>,
+    /// Terms and conditions, in Markdown.
+    pub terms: Option<String>,
+    /// Text printed at the end of the invoice, in Markdown.
+    pub footer: Option<String>,
 }
 
 /// Invoice metadata.
 #[derive(Debug, Deserialize)]
 pub struct Metadata {
+    #[serde(default)]
+    pub kind: DocumentKind,
     pub invoice_id: String,
     pub issue_date: NaiveDate,
     pub payment_terms: String,
-    pub tax_rate: f64,
-    pub currency: String,
+    pub due_date: Option<NaiveDate>,
+    /// A single tax rate, as a percentage. Shorthand for a single tax.
+    pub tax_rate: Option<Decimal>,
+    /// The taxes levied on each item, in order.
+    #[serde(default)]
+    pub taxes: Vec<Tax>,
+    pub currency: Currency,
+    #[serde(default)]
+    pub rounding: Rounding,
+    /// Whether item prices already include tax.
+    #[serde(default)]
+    pub prices_include_tax: bool,
+    /// A markup on the items in `expenses`, as a percentage, for items that
+    /// don't set their own. Other sections set their own `markup`.
+    pub expense_markup: Option<Decimal>,
+    /// The currency tax is reported in, if different from the billing
+    /// currency.
+    pub reporting_currency: Option<Currency>,
+    /// The value of one unit of the billing currency in the reporting
+    /// currency.
+    pub exchange_rate: Option<Decimal>,
+    /// A CSV file of exchange rates to look up the rate on the issue date,
+    /// relative to the invoice file.
+    pub exchange_rates: Option<PathBuf>,
+    /// A TOML file of named rates shared between invoices, relative to the
+    /// invoice file.
+    pub rate_card_file: Option<PathBuf>,
+    /// The invoice a credit note is issued against.
+    pub credited_invoice: Option<CreditedInvoice>,
+    /// Extra fields shown with the metadata, in order, e.g. a project code.
+    #[serde(default)]
+    pub custom: Vec<CustomField>,
+}
+
+/// A custom metadata field.
+#[derive(Debug, Deserialize)]
+pub struct CustomField {
+    pub label: String,
+    pub value: String,
+}
+
+/// The kind of document being issued.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocumentKind {
+    #[default]
+    Invoice,
+    /// A credit note, which reverses all or part of an earlier invoice.
+    CreditNote,
+}
+
+/// A reference to the invoice a credit note is issued against.
+#[derive(Debug, Deserialize)]
+pub struct CreditedInvoice {
+    pub invoice_id: String,
+    pub issue_date: NaiveDate,
+}
+
+/// The invoice amounts converted to the reporting currency.
+#[derive(Debug, PartialEq)]
+pub struct ReportingAmounts {
+    pub currency: Currency,
+    pub exchange_rate: Decimal,
+    /// The total excluding tax, after discounts.
+    pub net: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+}
+
+/// A named tax.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tax {
+    pub name: String,
+    /// The tax rate, as a percentage.
+    pub rate: Decimal,
+    /// Whether this tax is levied on the amount plus the preceding taxes.
+    #[serde(default)]
+    pub compound: bool,
+}
+
+/// How amounts are rounded to the currency's minor units.
+#[derive(Debug, Default, Deserialize)]
+pub struct Rounding {
+    #[serde(default)]
+    pub tax: TaxRounding,
+    #[serde(default)]
+    pub mode: RoundingMode,
+}
+
+/// Whether tax is rounded once per line item or once for the whole invoice.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaxRounding {
+    /// Tax is calculated and rounded on each line, then summed.
+    PerLine,
+    /// Tax is calculated on the subtotal and rounded once.
+    #[default]
+    PerInvoice,
+}
+
+/// How to round midpoint values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundingMode {
+    /// Round midpoints away from zero.
+    #[default]
+    HalfUp,
+    /// Round midpoints to the nearest even digit (banker's rounding).
+    HalfEven,
+}
+
+impl RoundingMode {
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+        }
+    }
+}
+
+/// A tax code, which line items can use to override the default tax rate.
+#[derive(Debug, Deserialize)]
+pub struct TaxCode {
+    pub rate: Decimal,
+    pub description: Option<String>,
+}
+
+/// A named rate on the rate card, e.g. `senior-dev`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rate {
+    pub unit_price: Decimal,
+    pub unit: Option<String>,
+    pub tax_code: Option<String>,
+}
+
+/// A discount, either a percentage or a fixed amount.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Discount {
+    /// A percentage of the amount being discounted.
+    Percent(Decimal),
+    /// A fixed amount.
+    Amount(Decimal),
+}
+
+/// A discount on the whole invoice.
+#[derive(Debug, Deserialize)]
+pub struct InvoiceDiscount {
+    pub description: String,
+    #[serde(flatten)]
+    pub discount: Discount,
+}
+
+/// Tax the client withholds from the payment, e.g. PAYG withholding.
+#[derive(Debug, Deserialize)]
+pub struct Withholding {
+    pub name: String,
+    /// The withholding rate, as a percentage of the subtotal excluding tax.
+    pub rate: Decimal,
+}
+
+/// The tax owed on all the line items that share a tax code.
+#[derive(Debug, PartialEq)]
+pub struct TaxSummary {
+    /// The tax code, or `None` for items taxed at the default rate.
+    pub code: Option<String>,
+    /// The name of the tax.
+    pub name: String,
+    /// The tax rate, as a percentage.
+    pub rate: Decimal,
+    /// The amount this tax is levied on: the total of the items with this
+    /// tax code, plus the preceding taxes if the tax is compound.
+    pub base: Decimal,
+    /// The tax owed on those items.
+    pub tax: Decimal,
+}
+
+/// The total of one tax across the whole invoice.
+#[derive(Debug, PartialEq)]
+pub struct TaxTotal {
+    /// The name of the tax.
+    pub name: String,
+    /// The tax rate, as a percentage.
+    pub rate: Decimal,
+    /// The tax owed.
+    pub tax: Decimal,
 }
 
 /// The invoice's issuer.
@@ -47,33 +275,113 @@ pub struct Metadata {
 pub struct Issuer {
     pub name: String,
     pub email: String,
-    pub abn: String,
+    /// An Australian Business Number. Shorthand for an `abn` tax
+    /// registration.
+    pub abn: Option<String>,
+    pub tax_registration: Option<TaxRegistration>,
+    pub address: Option<Address>,
+}
+
+/// The issuer's tax registration.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TaxRegistration {
+    pub scheme: TaxScheme,
+    pub number: String,
+}
+
+/// A tax registration scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaxScheme {
+    /// An Australian Business Number.
+    Abn,
+    /// An EU or UK VAT identification number.
+    Vat,
+    /// A US Employer Identification Number.
+    Ein,
+    /// An Indian GST Identification Number.
+    Gstin,
+    /// A New Zealand Business Number.
+    Nzbn,
+    /// A Japanese qualified invoice issuer registration number.
+    TNumber,
 }
 
 /// The invoice's recipient.
 #[derive(Debug, Deserialize)]
 pub struct Recipient {
     pub name: String,
-    pub company: String,
+    pub company: Option<String>,
+    /// Who the invoice is for the attention of, e.g. `Accounts Payable`.
+    pub attention: Option<String>,
     pub email: String,
+    /// Further addresses to copy the invoice to.
+    #[serde(default)]
+    pub cc: Vec<String>,
+    /// The client's reference for the invoice, e.g. a purchase order number.
+    pub reference: Option<String>,
+    /// The recipient's tax registration number, e.g. their VAT number.
+    pub tax_id: Option<String>,
+    /// The recipient's country, as an ISO 3166-1 alpha-2 code. Defaults to
+    /// the address's country.
+    pub country: Option<String>,
+    pub address: Option<Address>,
+    #[serde(default)]
+    pub tax_status: TaxStatus,
+    /// Overrides the legal wording printed for exports and reverse-charge
+    /// supplies.
+    pub tax_notice: Option<String>,
+}
+
+/// How supplies to the recipient are taxed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaxStatus {
+    /// Taxes apply as normal.
+    #[default]
+    Domestic,
+    /// The supply is an export, and is zero-rated.
+    Export,
+    /// The recipient accounts for the tax, so none is charged.
+    ReverseCharge,
 }
 
-/// An entry in the labour list.
+/// A named section of line items, e.g. "Travel" or "Hardware".
 #[derive(Debug, Deserialize)]
-pub struct Labour {
-    pub date: NaiveDate,
-    pub description: String,
-    pub unit_price: f64,
-    pub quantity: u32,
+pub struct Section<Price = Decimal> {
+    pub heading: String,
+    /// A markup on the section's items, as a percentage, for items that
+    /// don't set their own.
+    pub markup: Option<Decimal>,
+    #[serde(default)]
+    pub items: Vec<LineItem<Price>>,
 }
 
-/// An entry in the expenses list.
+/// A line item.
 #[derive(Debug, Deserialize)]
-pub struct Expense {
+pub struct LineItem<Price = Decimal> {
     pub date: NaiveDate,
+    /// An item code or SKU.
+    pub sku: Option<String>,
     pub description: String,
-    pub unit_price: f64,
-    pub quantity: u32,
+    /// Further detail shown under the description, in Markdown, e.g. a list
+    /// of the tasks done.
+    pub details: Option<String>,
+    /// The name of a rate on the rate card, which supplies the unit price,
+    /// unit, and tax code if they're not given.
+    pub rate: Option<String>,
+    pub unit_price: Price,
+    pub quantity: Decimal,
+    /// The unit of measure, e.g. `hours`, `days`, or `km`.
+    pub unit: Option<String>,
+    pub tax_code: Option<String>,
+    pub discount: Option<Discount>,
+    /// A handling markup on the cost, as a percentage.
+    pub markup: Option<Decimal>,
+    /// Whether this is a cost passed through to the client at cost, which is
+    /// left out of the tax base.
+    #[serde(default)]
+    pub reimbursable: bool,
 }
 
 /// Payment information.
@@ -86,18 +394,183 @@ pub struct Payment {
     pub swift: String,
 }
 
-impl Labour {
-    /// Calculate the total for this item.
-    pub fn total(&self) -> f64 {
-        let quantity: f64 = self.quantity as f64;
-        self.unit_price * quantity
+/// A way the client can pay the invoice.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+pub enum PaymentMethod {
+    /// A transfer to an Australian bank account.
+    AuBank {
+        name: String,
+        bsb: String,
+        acct: String,
+        bank: String,
+        swift: Option<String>,
+    },
+    /// A transfer to an IBAN.
+    Iban {
+        name: String,
+        iban: String,
+        bic: Option<String>,
+        bank: Option<String>,
+    },
+    /// An ACH transfer to a US bank account.
+    Ach {
+        name: String,
+        routing_number: String,
+        account_number: String,
+        bank: Option<String>,
+    },
+    /// A transfer to a UK bank account.
+    UkBank {
+        name: String,
+        sort_code: String,
+        account_number: String,
+        bank: Option<String>,
+    },
+    /// A transfer to an Australian PayID.
+    PayId { name: String, pay_id: String },
+    /// A link to an online payment page.
+    Link { url: String, label: Option<String> },
+}
+
+/// A payment received against the invoice, e.g. a deposit.
+#[derive(Debug, Deserialize)]
+pub struct PaymentReceived {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub reference: Option<String>,
+}
+
+/// Charges that apply once the invoice is overdue.
+#[derive(Debug, Deserialize)]
+pub struct LateFee {
+    /// A flat fee charged once the invoice is overdue.
+    pub flat_fee: Option<Decimal>,
+    /// Simple interest charged daily on the balance due, as an annual
+    /// percentage.
+    pub annual_rate: Option<Decimal>,
+}
+
+impl Metadata {
+    /// The taxes levied on items without a tax code, in order.
+    pub fn taxes(&self) -> Vec<Tax> {
+        match self.tax_rate {
+            Some(rate) => vec![Tax {
+                name: "Tax".to_string(),
+                rate,
+                compound: false,
+            }],
+            None => self.taxes.clone(),
+        }
+    }
+}
+
+impl Issuer {
+    /// Whether the issuer is in Australia: registered with an ABN, or with an
+    /// Australian address.
+    pub fn is_australian(&self) -> bool {
+        let abn: bool = self
+            .tax_registration()
+            .is_some_and(|r| r.scheme == TaxScheme::Abn);
+        let address: bool = self
+            .address
+            .as_ref()
+            .is_some_and(|a| a.country.as_deref() == Some("AU"));
+        abn || address
+    }
+
+    /// The issuer's tax registration, if they're registered.
+    pub fn tax_registration(&self) -> Option<TaxRegistration> {
+        match &self.abn {
+            Some(abn) => Some(TaxRegistration {
+                scheme: TaxScheme::Abn,
+                number: abn.clone(),
+            }),
+            None => self.tax_registration.clone(),
+        }
+    }
+}
+
+impl TaxScheme {
+    /// The label printed before the registration number.
+    pub fn label(self) -> &'static str {
+        match self {
+            TaxScheme::Abn => "ABN",
+            TaxScheme::Vat => "VAT ID",
+            TaxScheme::Ein => "EIN",
+            TaxScheme::Gstin => "GSTIN",
+            TaxScheme::Nzbn => "NZBN",
+            TaxScheme::TNumber => "Registration No.",
+        }
+    }
+}
+
+impl Recipient {
+    /// The recipient's country, from the address if not given directly.
+    pub fn country(&self) -> Option<&str> {
+        self.country
+            .as_deref()
+            .or_else(|| self.address.as_ref()?.country.as_deref())
+    }
+
+    /// Whether supplies to the recipient are zero-rated.
+    pub fn is_zero_rated(&self) -> bool {
+        self.tax_status != TaxStatus::Domestic
+    }
+}
+
+impl Discount {
+    /// Calculate the unrounded discount on an amount.
+    pub fn amount_off(&self, amount: Decimal) -> Decimal {
+        match self {
+            Discount::Percent(percent) => amount * percent / Decimal::ONE_HUNDRED,
+            Discount::Amount(value) => *value,
+        }
+    }
+}
+
+impl LineItem<Option<Decimal>> {
+    /// Fill in the unit price, unit, and tax code from the item's rate, if
+    /// they're not given.
+    fn resolve(self, rate_card: &BTreeMap<String, Rate>) -> Fallible<LineItem> {
+        let rate: Option<&Rate> = match &self.rate {
+            Some(name) => Some(rate_card.get(name).ok_or_else(|| {
+                ScriptError::new(format!(
+                    "line item `{}` has an unknown rate: {name}",
+                    self.description
+                ))
+            })?),
+            None => None,
+        };
+        let Some(unit_price) = self.unit_price.or(rate.map(|r| r.unit_price)) else {
+            return Err(ScriptError::new(format!(
+                "line item `{}` needs a `unit_price` or a `rate`",
+                self.description
+            )));
+        };
+        Ok(LineItem {
+            date: self.date,
+            sku: self.sku,
+            description: self.description,
+            details: self.details,
+            rate: self.rate,
+            unit_price,
+            quantity: self.quantity,
+            unit: self.unit.or_else(|| rate.and_then(|r| r.unit.clone())),
+            tax_code: self
+                .tax_code
+                .or_else(|| rate.and_then(|r| r.tax_code.clone())),
+            discount: self.discount,
+            markup: self.markup,
+            reimbursable: self.reimbursable,
+        })
     }
 }
 
-impl Expense {
+impl LineItem {
     /// Calculate the total for this item.
-    pub fn total(&self) -> f64 {
-        self.unit_price * self.quantity as f64
+    pub fn total(&self) -> Decimal {
+        self.unit_price * self.quantity
     }
 }
 
@@ -105,269 +578,2958 @@ impl Invoice {
     /// Parse an invoice from the path to a TOML file.
     pub fn parse(path: &Path) -> Fallible<Self> {
         let toml_content: String = std::fs::read_to_string(path)?;
-        let invoice: Invoice = toml::from_str(&toml_content)?;
+        let invoice: Invoice<Option<Decimal>> = toml::from_str(&toml_content)?;
+        let invoice: Invoice = invoice.resolve(path.parent().unwrap_or(Path::new(".")))?;
+        invoice.validate()?;
         Ok(invoice)
     }
 
     /// Parse an invoice from a string.
     #[cfg(test)]
     pub fn parse_string(text: &str) -> Fallible<Self> {
-        let invoice: Invoice = toml::from_str(text)?;
+        let invoice: Invoice<Option<Decimal>> = toml::from_str(text)?;
+        let invoice: Invoice = invoice.resolve(Path::new("."))?;
+        invoice.validate()?;
         Ok(invoice)
     }
 
-    /// Calculate the subtotal: the total cost of all invoice items.
-    pub fn subtotal(&self) -> f64 {
-        let labour_total: f64 = self.labour.iter().map(|l| l.total()).sum();
-        let expenses_total: f64 = self.expenses.iter().map(|e| e.total()).sum();
-        labour_total + expenses_total
+    /// Check the invoice for errors that can't be caught while parsing.
+    fn validate(&self) -> Fallible<()> {
+        match (self.metadata.kind, &self.metadata.credited_invoice) {
+            (DocumentKind::CreditNote, None) => {
+                return Err(ScriptError::new(
+                    "a credit note requires a `credited_invoice`",
+                ));
+            }
+            (DocumentKind::Invoice, Some(_)) => {
+                return Err(ScriptError::new(
+                    "only credit notes can have a `credited_invoice`",
+                ));
+            }
+            _ => {}
+        }
+        if self.issuer.abn.is_some() && self.issuer.tax_registration.is_some() {
+            return Err(ScriptError::new(
+                "issuer can't have both `abn` and `tax_registration`",
+            ));
+        }
+        if self.payment.is_none() && self.payment_methods.is_empty() {
+            return Err(ScriptError::new(
+                "an invoice must have a `payment` account or `payment_methods`",
+            ));
+        }
+        if self.metadata.reporting_currency.is_some() != self.metadata.exchange_rate.is_some() {
+            return Err(ScriptError::new(
+                "a `reporting_currency` requires an exchange rate, and vice versa",
+            ));
+        }
+        if self.recipient.is_zero_rated() && self.recipient.country().is_none() {
+            return Err(ScriptError::new(
+                "exports and reverse-charge supplies require a recipient country",
+            ));
+        }
+        if let Some(due_date) = self.metadata.due_date
+            && due_date < self.metadata.issue_date
+        {
+            return Err(ScriptError::new("the due date is before the issue date"));
+        }
+        if self.metadata.due_date.is_none()
+            && let Some(terms) = PaymentTerms::parse(&self.metadata.payment_terms)
+            && terms.due_date(self.metadata.issue_date).is_none()
+        {
+            return Err(ScriptError::new(format!(
+                "payment terms `{}` put the due date out of range",
+                self.metadata.payment_terms
+            )));
+        }
+        if self.late_fee.is_some() && self.due_date().is_none() {
+            return Err(ScriptError::new("a late fee requires a due date"));
+        }
+        match (&self.metadata.tax_rate, self.metadata.taxes.is_empty()) {
+            (Some(_), false) => {
+                return Err(ScriptError::new(
+                    "metadata can't have both `tax_rate` and `taxes`",
+                ));
+            }
+            (None, true) => {
+                return Err(ScriptError::new(
+                    "metadata must have either `tax_rate` or `taxes`",
+                ));
+            }
+            _ => {}
+        }
+        let tax_rates = self.metadata.tax_rate.iter();
+        let tax_rates = tax_rates.chain(self.metadata.taxes.iter().map(|t| &t.rate));
+        let tax_rates = tax_rates.chain(self.tax_codes.values().map(|c| &c.rate));
+        for rate in tax_rates {
+            if *rate < Decimal::ZERO {
+                return Err(ScriptError::new("tax rates can't be negative"));
+            }
+        }
+        for item in self.items() {
+            if item.quantity < Decimal::ZERO {
+                return Err(ScriptError::new(format!(
+                    "line item `{}` has a negative quantity",
+                    item.description
+                )));
+            }
+            if let Some(code) = &item.tax_code
+                && !self.tax_codes.contains_key(code)
+            {
+                return Err(ScriptError::new(format!("unknown tax code: {code}")));
+            }
+            if let Some(markup) = item.markup {
+                if item.reimbursable {
+                    return Err(ScriptError::new(
+                        "reimbursable items are passed through at cost, and can't have a markup",
+                    ));
+                }
+                if markup < Decimal::ZERO {
+                    return Err(ScriptError::new("markups can't be negative"));
+                }
+            }
+        }
+        let line_discounts = self.items().filter_map(|item| item.discount.as_ref());
+        let invoice_discounts = self.discounts.iter().map(|d| &d.discount);
+        for discount in line_discounts.chain(invoice_discounts) {
+            let valid: bool = match discount {
+                Discount::Percent(percent) => {
+                    *percent >= Decimal::ZERO && *percent <= Decimal::ONE_HUNDRED
+                }
+                Discount::Amount(amount) => *amount >= Decimal::ZERO,
+            };
+            if !valid {
+                return Err(ScriptError::new(
+                    "discounts can't be negative, and percentages can't exceed 100",
+                ));
+            }
+        }
+        for item in self.items() {
+            if item.discount.is_some() && self.line_discount(item) > self.line_total(item) {
+                return Err(ScriptError::new("discount exceeds the line item total"));
+            }
+        }
+        if self.discount_amount().abs() > self.subtotal().abs() {
+            return Err(ScriptError::new("discounts exceed the subtotal"));
+        }
+        Ok(())
+    }
+
+    /// The legal wording explaining why no tax is charged, if any. Australian
+    /// issuers describe exports as GST-free.
+    pub fn tax_notice(&self) -> Option<String> {
+        if let Some(notice) = &self.recipient.tax_notice {
+            return Some(notice.clone());
+        }
+        let country: &str = self
+            .recipient
+            .country()
+            .map(country_name)
+            .unwrap_or("another country");
+        match self.recipient.tax_status {
+            TaxStatus::Domestic => None,
+            TaxStatus::Export if self.issuer.is_australian() => Some(format!(
+                "GST-free export: services supplied to a recipient in {country}."
+            )),
+            TaxStatus::Export => Some(format!(
+                "Zero-rated export: services supplied to a recipient in {country}."
+            )),
+            TaxStatus::ReverseCharge => {
+                Some("Reverse charge: VAT to be accounted for by the recipient.".to_string())
+            }
+        }
+    }
+
+    /// The country the issuer and the recipient are both in, if they are,
+    /// which addresses leave out.
+    pub fn home_country(&self) -> Option<&str> {
+        let issuer: Option<&str> = self.issuer.address.as_ref()?.country.as_deref();
+        issuer.filter(|country| self.recipient.country() == Some(*country))
+    }
+
+    /// Whether this is a credit note.
+    pub fn is_credit_note(&self) -> bool {
+        self.metadata.kind == DocumentKind::CreditNote
+    }
+
+    /// The sign of the invoice's amounts: negative for credit notes, whose
+    /// amounts are credits.
+    pub fn sign(&self) -> Decimal {
+        if self.is_credit_note() {
+            Decimal::NEGATIVE_ONE
+        } else {
+            Decimal::ONE
+        }
+    }
+
+    /// The ways the client can pay, starting with the `payment` account.
+    pub fn payment_methods(&self) -> Vec<PaymentMethod> {
+        let legacy = self.payment.iter().map(|p| PaymentMethod::AuBank {
+            name: p.name.clone(),
+            bsb: p.bsb.clone(),
+            acct: p.acct.clone(),
+            bank: p.bank.clone(),
+            swift: Some(p.swift.clone()),
+        });
+        legacy.chain(self.payment_methods.iter().cloned()).collect()
+    }
+
+    /// The date payment is due: the explicit due date if there is one,
+    /// otherwise computed from the payment terms and the issue date.
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        self.metadata.due_date.or_else(|| {
+            PaymentTerms::parse(&self.metadata.payment_terms)
+                .and_then(|terms| terms.due_date(self.metadata.issue_date))
+        })
+    }
+
+    /// The heading and items of every section, in order: labour, expenses,
+    /// then the other sections.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &[LineItem])> {
+        let labour = std::iter::once(("Labour", self.labour.as_slice()));
+        let expenses = std::iter::once(("Expenses", self.expenses.as_slice()));
+        let sections = self
+            .sections
+            .iter()
+            .map(|s| (s.heading.as_str(), s.items.as_slice()));
+        labour.chain(expenses).chain(sections)
+    }
+
+    /// Every line item, in order.
+    fn items(&self) -> impl Iterator<Item = &LineItem> {
+        self.sections().flat_map(|(_, items)| items)
+    }
+
+    /// Round an amount to the currency's minor units, using the invoice's
+    /// rounding mode.
+    pub fn round(&self, amount: Decimal) -> Decimal {
+        let strategy: RoundingStrategy = self.metadata.rounding.mode.strategy();
+        self.metadata.currency.round(amount, strategy)
+    }
+
+    /// Calculate the rounded markup on a line item.
+    pub fn markup_amount(&self, item: &LineItem) -> Decimal {
+        match item.markup {
+            Some(markup) => self.round(self.round(item.total()) * markup / Decimal::ONE_HUNDRED),
+            None => Decimal::ZERO,
+        }
+    }
+
+    /// The rounded total of a line item, including any markup.
+    pub fn line_total(&self, item: &LineItem) -> Decimal {
+        self.round(item.total()) + self.markup_amount(item)
+    }
+
+    /// Calculate the rounded discount on a line item, including any markup.
+    pub fn line_discount(&self, item: &LineItem) -> Decimal {
+        match &item.discount {
+            Some(discount) => self.round(discount.amount_off(self.line_total(item))),
+            None => Decimal::ZERO,
+        }
+    }
+
+    /// Every line item with its rounded total, less any discount. On a credit
+    /// note, the totals are negative.
+    fn line_items(&self) -> impl Iterator<Item = (&LineItem, Decimal)> + '_ {
+        self.items().map(|item| {
+            let amount: Decimal = self.line_total(item) - self.line_discount(item);
+            (item, self.sign() * amount)
+        })
+    }
+
+    /// The tax code and taxable amount of every line item: its total less any
+    /// discount, with the invoice discounts spread across the items in
+    /// proportion to their totals. Reimbursable items are left out.
+    fn taxable_items(&self) -> Vec<(Option<&str>, Decimal)> {
+        let items: Vec<(&LineItem, Decimal)> = self.line_items().collect();
+        let subtotal: Decimal = self.subtotal();
+        let discount: Decimal = self.discount_amount();
+        let spread: bool = !discount.is_zero() && !subtotal.is_zero();
+        let mut remaining: Decimal = discount;
+        let last: usize = items.len().saturating_sub(1);
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, (item, total))| {
+                let share: Decimal = if !spread {
+                    Decimal::ZERO
+                } else if i == last {
+                    remaining
+                } else {
+                    self.round(discount * total / subtotal)
+                };
+                remaining -= share;
+                (item, total - share)
+            })
+            .filter(|(item, _)| !item.reimbursable)
+            .map(|(item, total)| (item.tax_code.as_deref(), total))
+            .collect()
+    }
+
+    /// Calculate the subtotal: the total cost of all invoice items, after
+    /// line item discounts.
+    pub fn subtotal(&self) -> Decimal {
+        self.line_items().map(|(_, total)| total).sum()
+    }
+
+    /// Calculate the amount of each invoice discount. Percentage discounts
+    /// apply to the subtotal less the preceding discounts.
+    pub fn discount_amounts(&self) -> Vec<Decimal> {
+        let mut remaining: Decimal = self.subtotal();
+        let mut amounts: Vec<Decimal> = Vec::new();
+        for discount in &self.discounts {
+            let amount: Decimal =
+                self.sign() * self.round(discount.discount.amount_off(self.sign() * remaining));
+            remaining -= amount;
+            amounts.push(amount);
+        }
+        amounts
+    }
+
+    /// Calculate the total of the invoice discounts.
+    pub fn discount_amount(&self) -> Decimal {
+        self.discount_amounts().iter().sum()
+    }
+
+    /// The taxes levied on items without a tax code, in order. The rates are
+    /// zero if supplies to the recipient are zero-rated.
+    pub fn taxes(&self) -> Vec<Tax> {
+        self.zero_rate(self.metadata.taxes())
+    }
+
+    /// Set the rates of the taxes to zero if supplies to the recipient are
+    /// zero-rated.
+    fn zero_rate(&self, mut taxes: Vec<Tax>) -> Vec<Tax> {
+        if self.recipient.is_zero_rated() {
+            for tax in &mut taxes {
+                tax.rate = Decimal::ZERO;
+            }
+        }
+        taxes
+    }
+
+    /// Calculate the tax owed for each tax code used in the invoice, with
+    /// one entry per tax. Items without a tax code come first.
+    pub fn tax_summary(&self) -> Vec<TaxSummary> {
+        let default = std::iter::once((None, self.taxes()));
+        let codes = self.tax_codes.iter().map(|(code, tc)| {
+            let tax = Tax {
+                name: code.clone(),
+                rate: tc.rate,
+                compound: false,
+            };
+            (Some(code.as_str()), self.zero_rate(vec![tax]))
+        });
+        let groups = default.chain(codes);
+        let taxable_items: Vec<(Option<&str>, Decimal)> = self.taxable_items();
+        let mut summary: Vec<TaxSummary> = Vec::new();
+        for (code, taxes) in groups {
+            let totals: Vec<Decimal> = taxable_items
+                .iter()
+                .filter(|(c, _)| *c == code)
+                .map(|(_, total)| *total)
+                .collect();
+            if totals.is_empty() {
+                continue;
+            }
+            let sum: Decimal = totals.iter().sum();
+            let amounts: Vec<Decimal> = match self.metadata.rounding.tax {
+                TaxRounding::PerLine => {
+                    let mut amounts: Vec<Decimal> = vec![Decimal::ZERO; taxes.len()];
+                    for total in &totals {
+                        for (acc, amount) in amounts.iter_mut().zip(self.levy(*total, &taxes)) {
+                            *acc += amount;
+                        }
+                    }
+                    amounts
+                }
+                TaxRounding::PerInvoice => self.levy(sum, &taxes),
+            };
+            let net: Decimal = if self.metadata.prices_include_tax {
+                sum - amounts.iter().sum::<Decimal>()
+            } else {
+                sum
+            };
+            let mut levied: Decimal = Decimal::ZERO;
+            for (tax, amount) in taxes.iter().zip(amounts) {
+                let base: Decimal = if tax.compound { net + levied } else { net };
+                summary.push(TaxSummary {
+                    code: code.map(str::to_string),
+                    name: tax.name.clone(),
+                    rate: tax.rate,
+                    base,
+                    tax: amount,
+                });
+                levied += amount;
+            }
+        }
+        summary
+    }
+
+    /// The total of each tax, summed across tax codes, in the order the
+    /// taxes are levied.
+    pub fn tax_totals(&self) -> Vec<TaxTotal> {
+        let mut totals: Vec<TaxTotal> = Vec::new();
+        for line in self.tax_summary() {
+            match totals
+                .iter_mut()
+                .find(|t| t.name == line.name && t.rate == line.rate)
+            {
+                Some(total) => total.tax += line.tax,
+                None => totals.push(TaxTotal {
+                    name: line.name,
+                    rate: line.rate,
+                    tax: line.tax,
+                }),
+            }
+        }
+        totals
+    }
+
+    /// Calculate the rounded amount of each tax levied on an amount. If prices
+    /// include tax, the taxes are backed out of the amount.
+    fn levy(&self, amount: Decimal, taxes: &[Tax]) -> Vec<Decimal> {
+        let net: Decimal = if self.metadata.prices_include_tax {
+            amount / gross_multiplier(taxes)
+        } else {
+            amount
+        };
+        let mut amounts: Vec<Decimal> = Vec::new();
+        for tax in taxes {
+            let base: Decimal = if tax.compound {
+                net + amounts.iter().sum::<Decimal>()
+            } else {
+                net
+            };
+            amounts.push(self.round(base * tax.rate / Decimal::ONE_HUNDRED));
+        }
+        amounts
     }
 
     /// Calculate the amount owed in tax.
-    pub fn tax_amount(&self) -> f64 {
-        self.subtotal() * (self.metadata.tax_rate / 100.0)
+    pub fn tax_amount(&self) -> Decimal {
+        self.tax_summary().iter().map(|s| s.tax).sum()
+    }
+
+    /// The total amount due: the subtotal less discounts, plus the tax
+    /// amount unless prices already include tax.
+    pub fn total(&self) -> Decimal {
+        let discounted: Decimal = self.subtotal() - self.discount_amount();
+        if self.metadata.prices_include_tax {
+            discounted
+        } else {
+            discounted + self.tax_amount()
+        }
+    }
+
+    /// Convert the total excluding tax, the tax, and the total to the
+    /// reporting currency, if there is one. The converted net amount and tax
+    /// are rounded separately, and add up to the converted total.
+    pub fn reporting_amounts(&self) -> Option<ReportingAmounts> {
+        let currency: Currency = self.metadata.reporting_currency?;
+        let exchange_rate: Decimal = self.metadata.exchange_rate?;
+        let strategy: RoundingStrategy = self.metadata.rounding.mode.strategy();
+        let convert = |amount: Decimal| currency.round(amount * exchange_rate, strategy);
+        let net: Decimal = convert(self.total() - self.tax_amount());
+        let tax: Decimal = convert(self.tax_amount());
+        Some(ReportingAmounts {
+            currency,
+            exchange_rate,
+            net,
+            tax,
+            total: net + tax,
+        })
+    }
+
+    /// Calculate the amount withheld by the client: a percentage of the
+    /// subtotal, excluding tax.
+    pub fn withholding_amount(&self) -> Decimal {
+        match &self.withholding {
+            Some(withholding) => {
+                let net: Decimal = self.total() - self.tax_amount();
+                self.round(net * withholding.rate / Decimal::ONE_HUNDRED)
+            }
+            None => Decimal::ZERO,
+        }
+    }
+
+    /// The amount the client pays: the total minus any withholding.
+    pub fn amount_payable(&self) -> Decimal {
+        self.total() - self.withholding_amount()
+    }
+
+    /// Calculate the total of the payments already received. On a credit
+    /// note, these are refunds paid to the client.
+    pub fn amount_paid(&self) -> Decimal {
+        self.sign()
+            * self
+                .payments_received
+                .iter()
+                .map(|p| p.amount)
+                .sum::<Decimal>()
+    }
+
+    /// The amount still outstanding: the amount payable less the payments
+    /// already received.
+    pub fn balance_due(&self) -> Decimal {
+        self.amount_payable() - self.amount_paid()
+    }
+
+    /// The number of days the invoice is overdue as of a given date, or zero
+    /// if it's not overdue.
+    pub fn days_overdue(&self, as_of: NaiveDate) -> i64 {
+        match self.due_date() {
+            Some(due_date) => (as_of - due_date).num_days().max(0),
+            None => 0,
+        }
     }
 
-    /// The total amount due: the subtotal plus the tax amount.
-    pub fn total(&self) -> f64 {
-        self.subtotal() + self.tax_amount()
+    /// Calculate the flat late fee owed as of a given date.
+    pub fn late_fee(&self, as_of: NaiveDate) -> Decimal {
+        match &self.late_fee {
+            Some(policy) if self.is_overdue(as_of) => policy.flat_fee.unwrap_or(Decimal::ZERO),
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Calculate the interest accrued on the balance due as of a given date.
+    pub fn late_interest(&self, as_of: NaiveDate) -> Decimal {
+        match &self.late_fee {
+            Some(LateFee {
+                annual_rate: Some(rate),
+                ..
+            }) if self.is_overdue(as_of) => {
+                let days: Decimal = Decimal::from(self.days_overdue(as_of));
+                let year: Decimal = Decimal::from(365);
+                self.round(self.balance_due() * rate / Decimal::ONE_HUNDRED * days / year)
+            }
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// The amount outstanding as of a given date, including late charges.
+    pub fn balance_due_as_of(&self, as_of: NaiveDate) -> Decimal {
+        self.balance_due() + self.late_fee(as_of) + self.late_interest(as_of)
+    }
+
+    /// Whether there is a balance due past the due date.
+    fn is_overdue(&self, as_of: NaiveDate) -> bool {
+        self.days_overdue(as_of) > 0 && self.balance_due() > Decimal::ZERO
+    }
+}
+
+impl Invoice<Option<Decimal>> {
+    /// Price the line items from the rate card, fill in invoice-wide
+    /// defaults, and load the data the invoice refers to from other files.
+    /// Paths are relative to `dir`.
+    fn resolve(self, dir: &Path) -> Fallible<Invoice> {
+        let mut rate_card: BTreeMap<String, Rate> = self.rate_card;
+        if let Some(path) = &self.metadata.rate_card_file {
+            let text: String = std::fs::read_to_string(dir.join(path))?;
+            let shared: BTreeMap<String, Rate> = toml::from_str(&text)?;
+            for (name, rate) in shared {
+                rate_card.entry(name).or_insert(rate);
+            }
+        }
+        let resolve_items = |items: Vec<LineItem<Option<Decimal>>>| -> Fallible<Vec<LineItem>> {
+            items
+                .into_iter()
+                .map(|item| item.resolve(&rate_card))
+                .collect()
+        };
+        let labour: Vec<LineItem> = resolve_items(self.labour)?;
+        let expenses: Vec<LineItem> = resolve_items(self.expenses)?;
+        let mut sections: Vec<Section> = Vec::new();
+        for section in self.sections {
+            sections.push(Section {
+                heading: section.heading,
+                markup: section.markup,
+                items: resolve_items(section.items)?,
+            });
+        }
+        let mut invoice: Invoice = Invoice {
+            metadata: self.metadata,
+            issuer: self.issuer,
+            recipient: self.recipient,
+            labour,
+            expenses,
+            sections,
+            payment: self.payment,
+            payment_methods: self.payment_methods,
+            tax_codes: self.tax_codes,
+            rate_card,
+            withholding: self.withholding,
+            discounts: self.discounts,
+            payments_received: self.payments_received,
+            late_fee: self.late_fee,
+            notes: self.notes,
+            terms: self.terms,
+            footer: self.footer,
+        };
+        apply_markup(&mut invoice.expenses, invoice.metadata.expense_markup);
+        for section in &mut invoice.sections {
+            apply_markup(&mut section.items, section.markup);
+        }
+        if let Some(rates) = &invoice.metadata.exchange_rates {
+            if invoice.metadata.exchange_rate.is_some() {
+                return Err(ScriptError::new(
+                    "metadata can't have both `exchange_rate` and `exchange_rates`",
+                ));
+            }
+            let Some(reporting_currency) = invoice.metadata.reporting_currency else {
+                return Err(ScriptError::new(
+                    "`exchange_rates` requires a `reporting_currency`",
+                ));
+            };
+            let rate: Decimal = lookup_rate(
+                &dir.join(rates),
+                invoice.metadata.currency.code(),
+                reporting_currency.code(),
+                invoice.metadata.issue_date,
+            )?;
+            invoice.metadata.exchange_rate = Some(rate);
+        }
+        Ok(invoice)
+    }
+}
+
+/// Give a default markup to the items that don't set their own, unless
+/// they're reimbursable.
+fn apply_markup(items: &mut [LineItem], markup: Option<Decimal>) {
+    if let Some(markup) = markup {
+        for item in items {
+            if !item.reimbursable && item.markup.is_none() {
+                item.markup = Some(markup);
+            }
+        }
+    }
+}
+
+/// The factor that turns a net amount into the gross amount, including all
+/// the given taxes.
+fn gross_multiplier(taxes: &[Tax]) -> Decimal {
+    let mut multiplier: Decimal = Decimal::ONE;
+    for tax in taxes {
+        let base: Decimal = if tax.compound {
+            multiplier
+        } else {
+            Decimal::ONE
+        };
+        multiplier += base * tax.rate / Decimal::ONE_HUNDRED;
     }
+    multiplier
 }
 
 #[cfg(test)]
 mod tests {
+    use rust_decimal_macros::dec;
+
     use super::*;
 
-    fn create_test_labour(unit_price: f64, quantity: u32) -> Labour {
-        Labour {
+    fn create_test_labour(unit_price: Decimal, quantity: u32) -> LineItem {
+        LineItem {
             date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            sku: None,
             description: "Test labour".to_string(),
+            details: None,
+            rate: None,
             unit_price,
-            quantity,
+            quantity: Decimal::from(quantity),
+            unit: None,
+            tax_code: None,
+            discount: None,
+            markup: None,
+            reimbursable: false,
         }
     }
 
-    fn create_test_expense(unit_price: f64, quantity: u32) -> Expense {
-        Expense {
+    fn create_test_expense(unit_price: Decimal, quantity: u32) -> LineItem {
+        LineItem {
             date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            sku: None,
             description: "Test expense".to_string(),
+            details: None,
+            rate: None,
             unit_price,
-            quantity,
+            quantity: Decimal::from(quantity),
+            unit: None,
+            tax_code: None,
+            discount: None,
+            markup: None,
+            reimbursable: false,
+        }
+    }
+
+    fn create_test_tax(name: &str, rate: Decimal, compound: bool) -> Tax {
+        Tax {
+            name: name.to_string(),
+            rate,
+            compound,
         }
     }
 
-    fn create_test_invoice(labour: Vec<Labour>, expenses: Vec<Expense>, tax_rate: f64) -> Invoice {
+    fn create_test_invoice(
+        labour: Vec<LineItem>,
+        expenses: Vec<LineItem>,
+        tax_rate: Decimal,
+    ) -> Invoice {
         Invoice {
             metadata: Metadata {
+                kind: DocumentKind::Invoice,
                 invoice_id: "TEST-001".to_string(),
                 issue_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
                 payment_terms: "Net 30".to_string(),
-                tax_rate,
-                currency: "USD".to_string(),
+                due_date: None,
+                tax_rate: Some(tax_rate),
+                taxes: vec![],
+                currency: Currency::from_code("USD").unwrap(),
+                rounding: Rounding::default(),
+                prices_include_tax: false,
+                expense_markup: None,
+                reporting_currency: None,
+                exchange_rate: None,
+                exchange_rates: None,
+                rate_card_file: None,
+                credited_invoice: None,
+                custom: vec![],
             },
             issuer: Issuer {
                 name: "Test Issuer".to_string(),
                 email: "issuer@test.com".to_string(),
-                abn: "123 456".to_string(),
+                abn: Some("123 456".to_string()),
+                tax_registration: None,
+                address: None,
             },
             recipient: Recipient {
                 name: "Test Recipient".to_string(),
-                company: "Test Company".to_string(),
+                company: Some("Test Company".to_string()),
+                attention: None,
                 email: "recipient@test.com".to_string(),
+                cc: vec![],
+                reference: None,
+                tax_id: None,
+                country: None,
+                address: None,
+                tax_status: TaxStatus::Domestic,
+                tax_notice: None,
             },
             labour,
             expenses,
-            payment: Payment {
+            sections: vec![],
+            payment: Some(Payment {
                 name: "Test Account".to_string(),
                 bsb: "123-456".to_string(),
                 acct: "12345678".to_string(),
                 bank: "Test Bank".to_string(),
                 swift: "TESTSWIFT".to_string(),
-            },
+            }),
+            payment_methods: vec![],
+            tax_codes: BTreeMap::new(),
+            rate_card: BTreeMap::new(),
+            withholding: None,
+            discounts: vec![],
+            payments_received: vec![],
+            late_fee: None,
+            notes: None,
+            terms: None,
+            footer: None,
         }
     }
 
-    /// Test Labour::total() with simple integer values (100 * 5 = 500)
+    /// Test LineItem::total() with simple integer values (100 * 5 = 500)
     #[test]
     fn test_labour_total_simple() {
-        let labour = create_test_labour(100.0, 5);
-        assert_eq!(labour.total(), 500.0);
+        let labour = create_test_labour(dec!(100.0), 5);
+        assert_eq!(labour.total(), dec!(500.0));
     }
 
-    /// Test Labour::total() with decimal prices (75.50 * 3 = 226.5)
+    /// Test LineItem::total() with decimal prices (75.50 * 3 = 226.5)
     #[test]
     fn test_labour_total_with_decimals() {
-        let labour = create_test_labour(75.50, 3);
-        assert_eq!(labour.total(), 226.5);
+        let labour = create_test_labour(dec!(75.50), 3);
+        assert_eq!(labour.total(), dec!(226.5));
     }
 
-    /// Test Labour::total() with zero quantity edge case.
+    /// Test LineItem::total() with zero quantity edge case.
     #[test]
     fn test_labour_total_zero_quantity() {
-        let labour = create_test_labour(100.0, 0);
-        assert_eq!(labour.total(), 0.0);
+        let labour = create_test_labour(dec!(100.0), 0);
+        assert_eq!(labour.total(), dec!(0.0));
     }
 
-    /// Test Labour::total() with single quantity.
+    /// Test LineItem::total() with single quantity.
     #[test]
     fn test_labour_total_single_quantity() {
-        let labour = create_test_labour(123.45, 1);
-        assert_eq!(labour.total(), 123.45);
+        let labour = create_test_labour(dec!(123.45), 1);
+        assert_eq!(labour.total(), dec!(123.45));
     }
 
-    /// Test Labour::total() with large quantity (50 * 10 = 500)
+    /// Test LineItem::total() with large quantity (50 * 10 = 500)
     #[test]
     fn test_labour_total_large_quantity() {
-        let labour = create_test_labour(50.0, 10);
-        assert_eq!(labour.total(), 500.0);
+        let labour = create_test_labour(dec!(50.0), 10);
+        assert_eq!(labour.total(), dec!(500.0));
     }
 
-    /// Test Expense::total() with simple calculation (25 * 4 = 100)
+    /// Test LineItem::total() with simple calculation (25 * 4 = 100)
     #[test]
     fn test_expense_total_simple() {
-        let expense = create_test_expense(25.0, 4);
-        assert_eq!(expense.total(), 100.0);
+        let expense = create_test_expense(dec!(25.0), 4);
+        assert_eq!(expense.total(), dec!(100.0));
     }
 
-    /// Test Expense::total() with decimal prices (12.99 * 7 = 90.93)
+    /// Test LineItem::total() with decimal prices (12.99 * 7 = 90.93)
     #[test]
     fn test_expense_total_with_decimals() {
-        let expense = create_test_expense(12.99, 7);
-        assert_eq!(expense.total(), 90.93);
+        let expense = create_test_expense(dec!(12.99), 7);
+        assert_eq!(expense.total(), dec!(90.93));
     }
 
-    /// Test Expense::total() with zero quantity edge case.
+    /// Test LineItem::total() with zero quantity edge case.
     #[test]
     fn test_expense_total_zero_quantity() {
-        let expense = create_test_expense(50.0, 0);
-        assert_eq!(expense.total(), 0.0);
+        let expense = create_test_expense(dec!(50.0), 0);
+        assert_eq!(expense.total(), dec!(0.0));
+    }
+
+    /// Test LineItem::total() with a fractional quantity (7.5 hours * 120 = 900)
+    #[test]
+    fn test_labour_total_fractional_quantity() {
+        let mut labour = create_test_labour(dec!(120.0), 0);
+        labour.quantity = dec!(7.5);
+        assert_eq!(labour.total(), dec!(900.0));
+    }
+
+    /// Test LineItem::total() with a fractional quantity (123.4 km * 0.88 = 108.592)
+    #[test]
+    fn test_expense_total_fractional_quantity() {
+        let mut expense = create_test_expense(dec!(0.88), 0);
+        expense.quantity = dec!(123.4);
+        assert_eq!(expense.total(), dec!(108.592));
+        let invoice = create_test_invoice(vec![], vec![expense], dec!(0.0));
+        assert_eq!(invoice.subtotal(), dec!(108.59));
     }
 
     /// Test Invoice::subtotal() with labour items only (500 + 150 = 650)
     #[test]
     fn test_invoice_subtotal_labour_only() {
-        let labour = vec![create_test_labour(100.0, 5), create_test_labour(75.0, 2)];
-        let invoice = create_test_invoice(labour, vec![], 10.0);
-        assert_eq!(invoice.subtotal(), 650.0); // 500 + 150
+        let labour = vec![
+            create_test_labour(dec!(100.0), 5),
+            create_test_labour(dec!(75.0), 2),
+        ];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(650.0)); // 500 + 150
     }
 
     /// Test Invoice::subtotal() with expense items only (100 + 100 = 200)
     #[test]
     fn test_invoice_subtotal_expenses_only() {
-        let expenses = vec![create_test_expense(25.0, 4), create_test_expense(50.0, 2)];
-        let invoice = create_test_invoice(vec![], expenses, 10.0);
-        assert_eq!(invoice.subtotal(), 200.0); // 100 + 100
+        let expenses = vec![
+            create_test_expense(dec!(25.0), 4),
+            create_test_expense(dec!(50.0), 2),
+        ];
+        let invoice = create_test_invoice(vec![], expenses, dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(200.0)); // 100 + 100
     }
 
     /// Test Invoice::subtotal() with combined labour and expenses (500 + 100 = 600)
     #[test]
     fn test_invoice_subtotal_labour_and_expenses() {
-        let labour = vec![create_test_labour(100.0, 5)];
-        let expenses = vec![create_test_expense(25.0, 4)];
-        let invoice = create_test_invoice(labour, expenses, 10.0);
-        assert_eq!(invoice.subtotal(), 600.0); // 500 + 100
+        let labour = vec![create_test_labour(dec!(100.0), 5)];
+        let expenses = vec![create_test_expense(dec!(25.0), 4)];
+        let invoice = create_test_invoice(labour, expenses, dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(600.0)); // 500 + 100
     }
 
     /// Test Invoice::subtotal() with empty invoice.
     #[test]
     fn test_invoice_subtotal_empty() {
-        let invoice = create_test_invoice(vec![], vec![], 10.0);
-        assert_eq!(invoice.subtotal(), 0.0);
+        let invoice = create_test_invoice(vec![], vec![], dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(0.0));
     }
 
     /// Test Invoice::subtotal() with multiple items of both types.
     #[test]
     fn test_invoice_subtotal_multiple_items() {
-        let labour = vec![create_test_labour(100.0, 1), create_test_labour(50.0, 1)];
-        let expenses = vec![create_test_expense(20.0, 1), create_test_expense(30.0, 1)];
-        let invoice = create_test_invoice(labour, expenses, 10.0);
-        assert_eq!(invoice.subtotal(), 200.0);
+        let labour = vec![
+            create_test_labour(dec!(100.0), 1),
+            create_test_labour(dec!(50.0), 1),
+        ];
+        let expenses = vec![
+            create_test_expense(dec!(20.0), 1),
+            create_test_expense(dec!(30.0), 1),
+        ];
+        let invoice = create_test_invoice(labour, expenses, dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(200.0));
     }
 
     /// Test Invoice::tax_amount() with standard 10% tax rate (10% of 1000 = 100)
     #[test]
     fn test_invoice_tax_amount_ten_percent() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 10.0);
-        assert_eq!(invoice.tax_amount(), 100.0); // 10% of 1000
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.tax_amount(), dec!(100.0)); // 10% of 1000
     }
 
     /// Test Invoice::tax_amount() with 20% tax rate (20% of 1000 = 200)
     #[test]
     fn test_invoice_tax_amount_twenty_percent() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 20.0);
-        assert_eq!(invoice.tax_amount(), 200.0); // 20% of 1000
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(20.0));
+        assert_eq!(invoice.tax_amount(), dec!(200.0)); // 20% of 1000
     }
 
     /// Test Invoice::tax_amount() with zero tax rate
     #[test]
     fn test_invoice_tax_amount_zero_rate() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 0.0);
-        assert_eq!(invoice.tax_amount(), 0.0);
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(0.0));
+        assert_eq!(invoice.tax_amount(), dec!(0.0));
     }
 
     /// Test Invoice::tax_amount() with fractional tax rate (7.5% of 1000 = 75)
     #[test]
     fn test_invoice_tax_amount_fractional_rate() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 7.5);
-        assert_eq!(invoice.tax_amount(), 75.0); // 7.5% of 1000
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(7.5));
+        assert_eq!(invoice.tax_amount(), dec!(75.0)); // 7.5% of 1000
     }
 
-    /// Test Invoice::tax_amount() with decimal subtotal using floating-point tolerance
+    /// Test Invoice::tax_amount() with decimal subtotal (10% of 99.99 = 9.999, rounded to 10.00)
     #[test]
     fn test_invoice_tax_amount_with_decimal_subtotal() {
-        let labour = vec![create_test_labour(33.33, 3)];
-        let invoice = create_test_invoice(labour, vec![], 10.0);
-        let expected_tax = 99.99 * 0.10;
-        assert!((invoice.tax_amount() - expected_tax).abs() < 0.001);
+        let labour = vec![create_test_labour(dec!(33.33), 3)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.tax_amount(), dec!(10.00));
     }
 
-    /// Test Invoice::total() with simple total and 10% tax (1000 + 100 = 1100)
+    /// Test Invoice::tax_amount() with per-invoice rounding (10% of 0.15 = 0.015, rounded to 0.02)
     #[test]
-    fn test_invoice_total_simple() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 10.0);
-        assert_eq!(invoice.total(), 1100.0); // 1000 subtotal + 100 tax
+    fn test_invoice_tax_amount_per_invoice_rounding() {
+        let expenses = vec![
+            create_test_expense(dec!(0.05), 1),
+            create_test_expense(dec!(0.05), 1),
+            create_test_expense(dec!(0.05), 1),
+        ];
+        let invoice = create_test_invoice(vec![], expenses, dec!(10.0));
+        assert_eq!(invoice.tax_amount(), dec!(0.02));
+        assert_eq!(invoice.total(), dec!(0.17));
     }
 
-    /// Test Invoice::total() with zero tax rate.
+    /// Test Invoice::tax_amount() with per-line rounding (3 * 0.005, each rounded to 0.01)
     #[test]
-    fn test_invoice_total_zero_tax() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 0.0);
-        assert_eq!(invoice.total(), 1000.0);
+    fn test_invoice_tax_amount_per_line_rounding() {
+        let expenses = vec![
+            create_test_expense(dec!(0.05), 1),
+            create_test_expense(dec!(0.05), 1),
+            create_test_expense(dec!(0.05), 1),
+        ];
+        let mut invoice = create_test_invoice(vec![], expenses, dec!(10.0));
+        invoice.metadata.rounding.tax = TaxRounding::PerLine;
+        assert_eq!(invoice.tax_amount(), dec!(0.03));
+        assert_eq!(invoice.total(), dec!(0.18));
     }
 
-    /// Test Invoice::total() with complex invoice containing multiple items and 15% tax
+    /// Test Invoice::tax_amount() with half-even rounding (10% of 0.25 = 0.025, rounded to 0.02)
     #[test]
-    fn test_invoice_total_complex() {
-        let labour = vec![create_test_labour(100.0, 5), create_test_labour(75.50, 4)];
-        let expenses = vec![create_test_expense(25.0, 6), create_test_expense(50.25, 2)];
-        let invoice = create_test_invoice(labour, expenses, 15.0);
-        // Subtotal: (500 + 302) + (150 + 100.5) = 1052.5
-        // Tax: 1052.5 * 0.15 = 157.875
-        // Total: 1052.5 + 157.875 = 1210.375
-        assert_eq!(invoice.subtotal(), 1052.5);
-        assert_eq!(invoice.tax_amount(), 157.875);
-        assert_eq!(invoice.total(), 1210.375);
+    fn test_invoice_tax_amount_half_even_rounding() {
+        let expenses = vec![create_test_expense(dec!(0.25), 1)];
+        let mut invoice = create_test_invoice(vec![], expenses, dec!(10.0));
+        assert_eq!(invoice.tax_amount(), dec!(0.03));
+        invoice.metadata.rounding.mode = RoundingMode::HalfEven;
+        assert_eq!(invoice.tax_amount(), dec!(0.02));
     }
 
-    /// Test Invoice::total() with empty invoice.
+    /// Test Invoice::subtotal() rounds each line total to the currency's minor units.
     #[test]
-    fn test_invoice_total_empty_invoice() {
-        let invoice = create_test_invoice(vec![], vec![], 10.0);
-        assert_eq!(invoice.total(), 0.0);
+    fn test_invoice_subtotal_rounds_lines() {
+        let expenses = vec![
+            create_test_expense(dec!(0.333), 1),
+            create_test_expense(dec!(0.333), 1),
+        ];
+        let invoice = create_test_invoice(vec![], expenses, dec!(0.0));
+        assert_eq!(invoice.subtotal(), dec!(0.66));
     }
 
-    /// Test Invoice::total() with high tax rate (25% of 1000 = 250, total = 1250)
+    /// Test Invoice::subtotal() does not accumulate floating-point error (0.1 + 0.2 = 0.3)
     #[test]
-    fn test_invoice_total_high_tax_rate() {
-        let labour = vec![create_test_labour(100.0, 10)];
-        let invoice = create_test_invoice(labour, vec![], 25.0);
-        assert_eq!(invoice.total(), 1250.0); // 1000 + 250
+    fn test_invoice_subtotal_exact() {
+        let expenses = vec![
+            create_test_expense(dec!(0.1), 1),
+            create_test_expense(dec!(0.2), 1),
+        ];
+        let invoice = create_test_invoice(vec![], expenses, dec!(0.0));
+        assert_eq!(invoice.subtotal(), dec!(0.3));
+    }
+
+    /// Test Invoice::tax_summary() groups items by tax code.
+    #[test]
+    fn test_invoice_tax_summary_by_code() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut expense = create_test_expense(dec!(50.0), 2);
+        expense.tax_code = Some("FRE".to_string());
+        let mut invoice = create_test_invoice(labour, vec![expense], dec!(10.0));
+        invoice.tax_codes.insert(
+            "FRE".to_string(),
+            TaxCode {
+                rate: dec!(0.0),
+                description: Some("GST-free".to_string()),
+            },
+        );
+        let expected = vec![
+            TaxSummary {
+                code: None,
+                name: "Tax".to_string(),
+                rate: dec!(10.0),
+                base: dec!(1000.0),
+                tax: dec!(100.0),
+            },
+            TaxSummary {
+                code: Some("FRE".to_string()),
+                name: "FRE".to_string(),
+                rate: dec!(0.0),
+                base: dec!(100.0),
+                tax: dec!(0.0),
+            },
+        ];
+        assert_eq!(invoice.tax_summary(), expected);
+        assert_eq!(invoice.tax_amount(), dec!(100.0));
+        assert_eq!(invoice.total(), dec!(1200.0));
+    }
+
+    /// Test Invoice::tax_summary() omits tax codes that no item uses.
+    #[test]
+    fn test_invoice_tax_summary_unused_code() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.tax_codes.insert(
+            "FRE".to_string(),
+            TaxCode {
+                rate: dec!(0.0),
+                description: None,
+            },
+        );
+        let summary = invoice.tax_summary();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].code, None);
+    }
+
+    /// Test Invoice::tax_amount() with tax-inclusive prices (1100 includes 100 of 10% tax)
+    #[test]
+    fn test_invoice_tax_amount_prices_include_tax() {
+        let labour = vec![create_test_labour(dec!(110.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.prices_include_tax = true;
+        assert_eq!(invoice.subtotal(), dec!(1100.0));
+        assert_eq!(invoice.tax_amount(), dec!(100.0));
+        assert_eq!(invoice.total(), dec!(1100.0));
+        assert_eq!(invoice.tax_summary()[0].base, dec!(1000.0));
+    }
+
+    /// Test Invoice::tax_amount() with tax-inclusive prices that don't divide evenly
+    #[test]
+    fn test_invoice_tax_amount_prices_include_tax_rounded() {
+        // 10/110 of 99.99 = 9.09
+        let labour = vec![create_test_labour(dec!(33.33), 3)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.prices_include_tax = true;
+        assert_eq!(invoice.tax_amount(), dec!(9.09));
+        assert_eq!(invoice.tax_summary()[0].base, dec!(90.90));
+        assert_eq!(invoice.total(), dec!(99.99));
+    }
+
+    /// Test Invoice::tax_summary() with stacked simple taxes (GST 5% + QST 9.975% of 1000)
+    #[test]
+    fn test_invoice_tax_summary_stacked() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(0.0));
+        invoice.metadata.tax_rate = None;
+        invoice.metadata.taxes = vec![
+            create_test_tax("GST", dec!(5.0), false),
+            create_test_tax("QST", dec!(9.975), false),
+        ];
+        let summary = invoice.tax_summary();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].name, "GST");
+        assert_eq!(summary[0].base, dec!(1000.0));
+        assert_eq!(summary[0].tax, dec!(50.00));
+        assert_eq!(summary[1].name, "QST");
+        assert_eq!(summary[1].base, dec!(1000.0));
+        assert_eq!(summary[1].tax, dec!(99.75));
+        assert_eq!(invoice.tax_amount(), dec!(149.75));
+        assert_eq!(invoice.total(), dec!(1149.75));
+    }
+
+    /// Test Invoice::tax_totals() gives one total per tax, with tax codes as
+    /// their own taxes.
+    #[test]
+    fn test_invoice_tax_totals() {
+        let labour = vec![
+            create_test_labour(dec!(100.0), 6),
+            create_test_labour(dec!(100.0), 4),
+        ];
+        let mut expense = create_test_expense(dec!(50.0), 2);
+        expense.tax_code = Some("FRE".to_string());
+        let mut invoice = create_test_invoice(labour, vec![expense], dec!(0.0));
+        invoice.metadata.tax_rate = None;
+        invoice.metadata.taxes = vec![
+            create_test_tax("GST", dec!(5.0), false),
+            create_test_tax("PST", dec!(7.0), false),
+        ];
+        invoice.tax_codes.insert(
+            "FRE".to_string(),
+            TaxCode {
+                rate: dec!(0.0),
+                description: None,
+            },
+        );
+        let total = |name: &str, rate: Decimal, tax: Decimal| TaxTotal {
+            name: name.to_string(),
+            rate,
+            tax,
+        };
+        let expected = vec![
+            total("GST", dec!(5.0), dec!(50.00)),
+            total("PST", dec!(7.0), dec!(70.00)),
+            total("FRE", dec!(0.0), dec!(0.00)),
+        ];
+        assert_eq!(invoice.tax_totals(), expected);
+    }
+
+    /// Test Invoice::tax_summary() with a compound tax (PST 10% levied on 1000 + 50 GST)
+    #[test]
+    fn test_invoice_tax_summary_compound() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(0.0));
+        invoice.metadata.tax_rate = None;
+        invoice.metadata.taxes = vec![
+            create_test_tax("GST", dec!(5.0), false),
+            create_test_tax("PST", dec!(10.0), true),
+        ];
+        let summary = invoice.tax_summary();
+        assert_eq!(summary[1].base, dec!(1050.0));
+        assert_eq!(summary[1].tax, dec!(105.00));
+        assert_eq!(invoice.tax_amount(), dec!(155.00));
+        assert_eq!(invoice.total(), dec!(1155.00));
+    }
+
+    /// Test Invoice::tax_summary() backs compound taxes out of tax-inclusive prices
+    #[test]
+    fn test_invoice_tax_summary_compound_prices_include_tax() {
+        let labour = vec![create_test_labour(dec!(1155.0), 1)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(0.0));
+        invoice.metadata.tax_rate = None;
+        invoice.metadata.taxes = vec![
+            create_test_tax("GST", dec!(5.0), false),
+            create_test_tax("PST", dec!(10.0), true),
+        ];
+        invoice.metadata.prices_include_tax = true;
+        let summary = invoice.tax_summary();
+        assert_eq!(summary[0].base, dec!(1000.0));
+        assert_eq!(summary[0].tax, dec!(50.00));
+        assert_eq!(summary[1].tax, dec!(105.00));
+        assert_eq!(invoice.total(), dec!(1155.0));
+    }
+
+    /// Test Invoice::total() with simple total and 10% tax (1000 + 100 = 1100)
+    #[test]
+    fn test_invoice_total_simple() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.total(), dec!(1100.0)); // 1000 subtotal + 100 tax
+    }
+
+    /// Test Invoice::total() with zero tax rate.
+    #[test]
+    fn test_invoice_total_zero_tax() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(0.0));
+        assert_eq!(invoice.total(), dec!(1000.0));
+    }
+
+    /// Test Invoice::total() with complex invoice containing multiple items and 15% tax
+    #[test]
+    fn test_invoice_total_complex() {
+        let labour = vec![
+            create_test_labour(dec!(100.0), 5),
+            create_test_labour(dec!(75.50), 4),
+        ];
+        let expenses = vec![
+            create_test_expense(dec!(25.0), 6),
+            create_test_expense(dec!(50.25), 2),
+        ];
+        let invoice = create_test_invoice(labour, expenses, dec!(15.0));
+        // Subtotal: (500 + 302) + (150 + 100.5) = 1052.5
+        // Tax: 1052.5 * 0.15 = 157.875, rounded to 157.88
+        // Total: 1052.5 + 157.88 = 1210.38
+        assert_eq!(invoice.subtotal(), dec!(1052.5));
+        assert_eq!(invoice.tax_amount(), dec!(157.88));
+        assert_eq!(invoice.total(), dec!(1210.38));
+    }
+
+    /// Test Invoice::total() with empty invoice.
+    #[test]
+    fn test_invoice_total_empty_invoice() {
+        let invoice = create_test_invoice(vec![], vec![], dec!(10.0));
+        assert_eq!(invoice.total(), dec!(0.0));
+    }
+
+    /// Test Invoice::total() with high tax rate (25% of 1000 = 250, total = 1250)
+    #[test]
+    fn test_invoice_total_high_tax_rate() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(25.0));
+        assert_eq!(invoice.total(), dec!(1250.0)); // 1000 + 250
+    }
+
+    /// Test Invoice::withholding_amount() with no withholding.
+    #[test]
+    fn test_invoice_withholding_none() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.withholding_amount(), dec!(0.0));
+        assert_eq!(invoice.amount_payable(), dec!(1100.0));
+    }
+
+    /// Test Invoice::withholding_amount() is levied on the subtotal excluding tax (47% of 1000)
+    #[test]
+    fn test_invoice_withholding() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.withholding = Some(Withholding {
+            name: "PAYG withholding".to_string(),
+            rate: dec!(47.0),
+        });
+        assert_eq!(invoice.withholding_amount(), dec!(470.00));
+        assert_eq!(invoice.total(), dec!(1100.0));
+        assert_eq!(invoice.amount_payable(), dec!(630.00));
+    }
+
+    /// Test Invoice::withholding_amount() excludes tax when prices include tax (15% of 1000)
+    #[test]
+    fn test_invoice_withholding_prices_include_tax() {
+        let labour = vec![create_test_labour(dec!(110.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.prices_include_tax = true;
+        invoice.withholding = Some(Withholding {
+            name: "IRPF".to_string(),
+            rate: dec!(15.0),
+        });
+        assert_eq!(invoice.withholding_amount(), dec!(150.00));
+        assert_eq!(invoice.amount_payable(), dec!(950.00));
+    }
+
+    /// Test Invoice::subtotal() with a percentage discount on a line item (1000 - 10%)
+    #[test]
+    fn test_invoice_line_discount_percent() {
+        let mut labour = create_test_labour(dec!(100.0), 10);
+        labour.discount = Some(Discount::Percent(dec!(10.0)));
+        let invoice = create_test_invoice(vec![labour], vec![], dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(900.00));
+        assert_eq!(invoice.tax_amount(), dec!(90.00));
+        assert_eq!(invoice.total(), dec!(990.00));
+    }
+
+    /// Test Invoice::subtotal() with a fixed discount on a line item (1000 - 250)
+    #[test]
+    fn test_invoice_line_discount_amount() {
+        let mut expense = create_test_expense(dec!(100.0), 10);
+        expense.discount = Some(Discount::Amount(dec!(250.0)));
+        let invoice = create_test_invoice(vec![], vec![expense], dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(750.00));
+        assert_eq!(invoice.total(), dec!(825.00));
+    }
+
+    /// Test Invoice::subtotal() with a markup on an expense (1000 + 15%)
+    #[test]
+    fn test_invoice_markup() {
+        let mut expense = create_test_expense(dec!(100.0), 10);
+        expense.markup = Some(dec!(15.0));
+        let invoice = create_test_invoice(vec![], vec![expense], dec!(10.0));
+        assert_eq!(invoice.markup_amount(&invoice.expenses[0]), dec!(150.00));
+        assert_eq!(invoice.subtotal(), dec!(1150.00));
+        assert_eq!(invoice.total(), dec!(1265.00));
+    }
+
+    /// Test Invoice::tax_amount() leaves reimbursable items out of the tax base
+    #[test]
+    fn test_invoice_reimbursable() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut expense = create_test_expense(dec!(200.0), 1);
+        expense.reimbursable = true;
+        let invoice = create_test_invoice(labour, vec![expense], dec!(10.0));
+        assert_eq!(invoice.subtotal(), dec!(1200.00));
+        assert_eq!(invoice.tax_amount(), dec!(100.00));
+        assert_eq!(invoice.total(), dec!(1300.00));
+    }
+
+    /// Test Invoice::discount_amounts() applies percentages after preceding discounts
+    #[test]
+    fn test_invoice_discounts_in_order() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.discounts = vec![
+            InvoiceDiscount {
+                description: "Loyalty".to_string(),
+                discount: Discount::Amount(dec!(100.0)),
+            },
+            InvoiceDiscount {
+                description: "Early payment".to_string(),
+                discount: Discount::Percent(dec!(10.0)),
+            },
+        ];
+        // 1000 - 100 = 900, 10% of 900 = 90
+        assert_eq!(invoice.discount_amounts(), vec![dec!(100.0), dec!(90.00)]);
+        assert_eq!(invoice.subtotal(), dec!(1000.0));
+        assert_eq!(invoice.tax_amount(), dec!(81.00));
+        assert_eq!(invoice.total(), dec!(891.00));
+    }
+
+    /// Test Invoice::tax_summary() spreads invoice discounts across tax codes
+    #[test]
+    fn test_invoice_discounts_spread_across_tax_codes() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut expense = create_test_expense(dec!(100.0), 10);
+        expense.tax_code = Some("FRE".to_string());
+        let mut invoice = create_test_invoice(labour, vec![expense], dec!(10.0));
+        invoice.tax_codes.insert(
+            "FRE".to_string(),
+            TaxCode {
+                rate: dec!(0.0),
+                description: None,
+            },
+        );
+        invoice.discounts = vec![InvoiceDiscount {
+            description: "Loyalty".to_string(),
+            discount: Discount::Percent(dec!(10.0)),
+        }];
+        let summary = invoice.tax_summary();
+        assert_eq!(summary[0].base, dec!(900.00));
+        assert_eq!(summary[0].tax, dec!(90.00));
+        assert_eq!(summary[1].base, dec!(900.00));
+        assert_eq!(invoice.total(), dec!(1890.00));
+    }
+
+    /// Test Invoice::balance_due() with no payments received.
+    #[test]
+    fn test_invoice_balance_due_no_payments() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.amount_paid(), dec!(0.0));
+        assert_eq!(invoice.balance_due(), dec!(1100.0));
+    }
+
+    /// Test Invoice::balance_due() subtracts deposits and partial payments (1100 - 300 - 200)
+    #[test]
+    fn test_invoice_balance_due_with_payments() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.payments_received = vec![
+            PaymentReceived {
+                date: NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+                amount: dec!(300.0),
+                reference: Some("Deposit".to_string()),
+            },
+            PaymentReceived {
+                date: NaiveDate::from_ymd_opt(2026, 1, 9).unwrap(),
+                amount: dec!(200.0),
+                reference: None,
+            },
+        ];
+        assert_eq!(invoice.total(), dec!(1100.0));
+        assert_eq!(invoice.amount_paid(), dec!(500.0));
+        assert_eq!(invoice.balance_due(), dec!(600.0));
+    }
+
+    /// Test Invoice::due_date() is computed from the payment terms.
+    #[test]
+    fn test_invoice_due_date_from_terms() {
+        let mut invoice = create_test_invoice(vec![], vec![], dec!(10.0));
+        assert_eq!(invoice.due_date(), NaiveDate::from_ymd_opt(2026, 1, 31));
+        invoice.metadata.payment_terms = "EOM + 15".to_string();
+        assert_eq!(invoice.due_date(), NaiveDate::from_ymd_opt(2026, 2, 15));
+        invoice.metadata.payment_terms = "By arrangement".to_string();
+        assert_eq!(invoice.due_date(), None);
+    }
+
+    /// Test Invoice::due_date() is `None` when the terms overflow the calendar.
+    #[test]
+    fn test_invoice_due_date_out_of_range() {
+        let mut invoice = create_test_invoice(vec![], vec![], dec!(10.0));
+        invoice.metadata.payment_terms = "NET 999999999".to_string();
+        assert_eq!(invoice.due_date(), None);
+        assert!(invoice.validate().is_err());
+    }
+
+    /// Test Invoice::due_date() prefers an explicit due date over the terms.
+    #[test]
+    fn test_invoice_due_date_explicit() {
+        let mut invoice = create_test_invoice(vec![], vec![], dec!(10.0));
+        invoice.metadata.due_date = NaiveDate::from_ymd_opt(2026, 1, 15);
+        assert_eq!(invoice.due_date(), NaiveDate::from_ymd_opt(2026, 1, 15));
+    }
+
+    /// Test Invoice::balance_due_as_of() before the due date has no late charges.
+    #[test]
+    fn test_invoice_late_charges_not_overdue() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.due_date = NaiveDate::from_ymd_opt(2026, 1, 31);
+        invoice.late_fee = Some(LateFee {
+            flat_fee: Some(dec!(50.0)),
+            annual_rate: Some(dec!(10.0)),
+        });
+        let as_of = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        assert_eq!(invoice.days_overdue(as_of), 0);
+        assert_eq!(invoice.late_fee(as_of), dec!(0.0));
+        assert_eq!(invoice.late_interest(as_of), dec!(0.0));
+        assert_eq!(invoice.balance_due_as_of(as_of), dec!(1100.0));
+    }
+
+    /// Test Invoice::balance_due_as_of() after the due date (1100 + 50 + 10% p.a. for 73 days)
+    #[test]
+    fn test_invoice_late_charges_overdue() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.due_date = NaiveDate::from_ymd_opt(2026, 1, 31);
+        invoice.late_fee = Some(LateFee {
+            flat_fee: Some(dec!(50.0)),
+            annual_rate: Some(dec!(10.0)),
+        });
+        let as_of = NaiveDate::from_ymd_opt(2026, 4, 14).unwrap();
+        assert_eq!(invoice.days_overdue(as_of), 73);
+        assert_eq!(invoice.late_fee(as_of), dec!(50.0));
+        // 1100 * 10% * 73 / 365 = 22
+        assert_eq!(invoice.late_interest(as_of), dec!(22.00));
+        assert_eq!(invoice.balance_due_as_of(as_of), dec!(1172.00));
+    }
+
+    /// Test Invoice::late_fee() is not charged once the invoice is paid.
+    #[test]
+    fn test_invoice_late_charges_paid() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.due_date = NaiveDate::from_ymd_opt(2026, 1, 31);
+        invoice.late_fee = Some(LateFee {
+            flat_fee: Some(dec!(50.0)),
+            annual_rate: None,
+        });
+        invoice.payments_received = vec![PaymentReceived {
+            date: NaiveDate::from_ymd_opt(2026, 1, 20).unwrap(),
+            amount: dec!(1100.0),
+            reference: None,
+        }];
+        let as_of = NaiveDate::from_ymd_opt(2026, 4, 14).unwrap();
+        assert_eq!(invoice.late_fee(as_of), dec!(0.0));
+        assert_eq!(invoice.balance_due_as_of(as_of), dec!(0.0));
+    }
+
+    /// Test Invoice::reporting_amounts() with no reporting currency.
+    #[test]
+    fn test_invoice_reporting_amounts_none() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        assert_eq!(invoice.reporting_amounts(), None);
+    }
+
+    /// Test Invoice::reporting_amounts() converts and rounds each figure separately.
+    #[test]
+    fn test_invoice_reporting_amounts() {
+        let labour = vec![create_test_labour(dec!(33.33), 3)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.reporting_currency = Currency::from_code("AUD");
+        invoice.metadata.exchange_rate = Some(dec!(1.5234));
+        let amounts = invoice.reporting_amounts().unwrap();
+        // 99.99 * 1.5234 = 152.324766
+        assert_eq!(amounts.net, dec!(152.32));
+        // 10.00 * 1.5234 = 15.234
+        assert_eq!(amounts.tax, dec!(15.23));
+        assert_eq!(amounts.total, dec!(167.55));
+    }
+
+    /// Test Invoice::reporting_amounts() converts the net amount after discounts.
+    #[test]
+    fn test_invoice_reporting_amounts_after_discounts() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.discounts = vec![InvoiceDiscount {
+            description: "Loyalty".to_string(),
+            discount: Discount::Amount(dec!(100.0)),
+        }];
+        invoice.metadata.reporting_currency = Currency::from_code("AUD");
+        invoice.metadata.exchange_rate = Some(dec!(2.0));
+        let amounts = invoice.reporting_amounts().unwrap();
+        assert_eq!(amounts.net, dec!(1800.00));
+        assert_eq!(amounts.tax, dec!(180.00));
+        assert_eq!(amounts.total, dec!(1980.00));
+    }
+
+    /// Test Invoice::tax_amount() is zero for exports.
+    #[test]
+    fn test_invoice_export_zero_rated() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.recipient.country = Some("US".to_string());
+        invoice.recipient.tax_status = TaxStatus::Export;
+        assert_eq!(invoice.tax_amount(), dec!(0.0));
+        assert_eq!(invoice.total(), dec!(1000.0));
+        assert_eq!(invoice.tax_summary()[0].rate, dec!(0.0));
+        assert_eq!(
+            invoice.tax_notice().unwrap(),
+            "GST-free export: services supplied to a recipient in the United States."
+        );
+    }
+
+    /// Test Invoice::tax_notice() uses the general wording for issuers outside
+    /// Australia.
+    #[test]
+    fn test_invoice_export_notice_outside_australia() {
+        let mut invoice = create_test_invoice(vec![], vec![], dec!(20.0));
+        invoice.issuer.abn = None;
+        invoice.issuer.tax_registration = Some(TaxRegistration {
+            scheme: TaxScheme::Vat,
+            number: "FR12345678901".to_string(),
+        });
+        invoice.recipient.country = Some("US".to_string());
+        invoice.recipient.tax_status = TaxStatus::Export;
+        assert_eq!(
+            invoice.tax_notice().unwrap(),
+            "Zero-rated export: services supplied to a recipient in the United States."
+        );
+    }
+
+    /// Test Invoice::tax_amount() is zero for reverse-charge supplies.
+    #[test]
+    fn test_invoice_reverse_charge() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(20.0));
+        invoice.recipient.country = Some("DE".to_string());
+        invoice.recipient.tax_status = TaxStatus::ReverseCharge;
+        assert_eq!(invoice.tax_amount(), dec!(0.0));
+        assert_eq!(
+            invoice.tax_notice().unwrap(),
+            "Reverse charge: VAT to be accounted for by the recipient."
+        );
+    }
+
+    /// Test a credit note's amounts are credits.
+    #[test]
+    fn test_credit_note_totals() {
+        let labour = vec![create_test_labour(dec!(100.0), 10)];
+        let mut invoice = create_test_invoice(labour, vec![], dec!(10.0));
+        invoice.metadata.kind = DocumentKind::CreditNote;
+        invoice.discounts = vec![InvoiceDiscount {
+            description: "Goodwill".to_string(),
+            discount: Discount::Amount(dec!(100.0)),
+        }];
+        assert_eq!(invoice.subtotal(), dec!(-1000.0));
+        assert_eq!(invoice.discount_amount(), dec!(-100.0));
+        assert_eq!(invoice.tax_amount(), dec!(-90.0));
+        assert_eq!(invoice.total(), dec!(-990.0));
+        invoice.payments_received = vec![PaymentReceived {
+            date: NaiveDate::from_ymd_opt(2026, 1, 15).unwrap(),
+            amount: dec!(990.0),
+            reference: None,
+        }];
+        assert_eq!(invoice.balance_due(), dec!(0.0));
+    }
+
+    /// Amounts in the TOML file are parsed as exact decimals.
+    #[test]
+    fn test_parse_invoice_exact_amounts() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[expenses]]
+            date        = "2052-06-21"
+            description = "Coffee"
+            unit_price  = 0.1
+            quantity    = 1
+
+            [[expenses]]
+            date        = "2052-06-21"
+            description = "Donut"
+            unit_price  = 0.2
+            quantity    = 1
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.subtotal(), dec!(0.3));
+        assert_eq!(invoice.tax_amount(), dec!(0.03));
+        assert_eq!(invoice.total(), dec!(0.33));
+        Ok(())
+    }
+
+    /// An invoice with an unknown currency code is rejected.
+    #[test]
+    fn test_parse_invoice_unknown_currency() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "XYZ"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An invoice with an item using an undefined tax code is rejected.
+    #[test]
+    fn test_parse_invoice_unknown_tax_code() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[expenses]]
+            date        = "2052-06-21"
+            description = "Flight to Freeside (steerage)"
+            unit_price  = 500.0
+            quantity    = 1
+            tax_code    = "INP"
+
+            [tax_codes.FRE]
+            rate        = 0.0
+            description = "GST-free"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An invoice with a list of taxes is parsed correctly.
+    #[test]
+    fn test_parse_invoice_taxes() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            currency      = "CAD"
+
+            [[metadata.taxes]]
+            name = "GST"
+            rate = 5.0
+
+            [[metadata.taxes]]
+            name     = "QST"
+            rate     = 9.975
+            compound = false
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.tax_amount(), dec!(179.70));
+        Ok(())
+    }
+
+    /// An invoice with both a tax rate and a list of taxes is rejected.
+    #[test]
+    fn test_parse_invoice_tax_rate_and_taxes() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "CAD"
+
+            [[metadata.taxes]]
+            name = "GST"
+            rate = 5.0
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An invoice with a negative tax rate is rejected.
+    #[test]
+    fn test_parse_invoice_negative_tax_rate() {
+        let text = r#"
+            [metadata]
+            invoice_id         = "1729"
+            issue_date         = "2052-06-30"
+            payment_terms      = "NET 30"
+            tax_rate           = -100.0
+            currency           = "AUD"
+            prices_include_tax = true
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-01"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An invoice with line item and invoice discounts is parsed correctly.
+    #[test]
+    fn test_parse_invoice_discounts() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+            discount    = { percent = 25.0 }
+
+            [[discounts]]
+            description = "Loyalty"
+            amount      = 100.0
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.subtotal(), dec!(900.00));
+        assert_eq!(invoice.discount_amount(), dec!(100.0));
+        assert_eq!(invoice.total(), dec!(880.00));
+        Ok(())
+    }
+
+    /// An invoice with a line item discount larger than the item is rejected.
+    #[test]
+    fn test_parse_invoice_discount_too_large() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+            discount    = { amount = 1300.0 }
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// A line item with a negative price and no discount is accepted.
+    #[test]
+    fn test_parse_invoice_negative_price() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [[expenses]]
+            date        = "2052-06-04"
+            description = "Goodwill credit"
+            unit_price  = -50.0
+            quantity    = 1
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.subtotal(), dec!(1150.00));
+        Ok(())
+    }
+
+    /// A line item with a negative quantity is rejected.
+    #[test]
+    fn test_parse_invoice_negative_quantity() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = -4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An invoice with fractional quantities and units is parsed correctly.
+    #[test]
+    fn test_parse_invoice_fractional_quantity() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 0.5
+            unit        = "days"
+
+            [[labour]]
+            date        = "2052-06-04"
+            description = "Sense/Net (cleanup)"
+            unit_price  = 300.0
+            quantity    = 2
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.labour[0].unit.as_deref(), Some("days"));
+        assert_eq!(invoice.subtotal(), dec!(750.0));
+        Ok(())
+    }
+
+    /// An exchange rate is looked up in a rates file next to the invoice.
+    #[test]
+    fn test_parse_invoice_exchange_rates_file() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id         = "1729"
+            issue_date         = "2052-06-30"
+            payment_terms      = "NET 30"
+            tax_rate           = 0.0
+            currency           = "USD"
+            reporting_currency = "AUD"
+            exchange_rates     = "rates.csv"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("invoice.toml"), text)?;
+        std::fs::write(
+            dir.path().join("rates.csv"),
+            "date,from,to,rate\n2052-06-01,USD,AUD,1.5\n2052-07-01,USD,AUD,1.6\n",
+        )?;
+        let invoice = Invoice::parse(&dir.path().join("invoice.toml"))?;
+        assert_eq!(invoice.metadata.exchange_rate, Some(dec!(1.5)));
+        assert_eq!(invoice.reporting_amounts().unwrap().total, dec!(1800.00));
+        Ok(())
+    }
+
+    /// A reporting currency without an exchange rate is rejected.
+    #[test]
+    fn test_parse_invoice_reporting_currency_without_rate() {
+        let text = r#"
+            [metadata]
+            invoice_id         = "1729"
+            issue_date         = "2052-06-30"
+            payment_terms      = "NET 30"
+            tax_rate           = 0.0
+            currency           = "USD"
+            reporting_currency = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// A reverse-charge supply without a recipient country is rejected.
+    #[test]
+    fn test_parse_invoice_reverse_charge_without_country() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 20.0
+            currency      = "EUR"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name       = "Wintermute"
+            company    = "Tessier-Ashpool S.A."
+            email      = "wmute@ta.sa"
+            tax_status = "reverse-charge"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// A credit note refers to the invoice it credits.
+    #[test]
+    fn test_parse_credit_note() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            kind          = "credit-note"
+            invoice_id    = "1730"
+            issue_date    = "2052-07-15"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+            credited_invoice = { invoice_id = "1729", issue_date = "2052-06-30" }
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-01"
+            description = "Cancelled work"
+            unit_price  = 100.0
+            quantity    = 2
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert!(invoice.is_credit_note());
+        assert_eq!(
+            invoice.metadata.credited_invoice.unwrap().invoice_id,
+            "1729"
+        );
+        Ok(())
+    }
+
+    /// A credit note without a reference to the credited invoice is rejected.
+    #[test]
+    fn test_parse_credit_note_without_reference() {
+        let text = r#"
+            [metadata]
+            kind          = "credit-note"
+            invoice_id    = "1730"
+            issue_date    = "2052-07-15"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// Custom sections follow labour and expenses, in the order given.
+    #[test]
+    fn test_parse_invoice_sections() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [[sections]]
+            heading = "Hardware"
+
+            [[sections.items]]
+            date        = "2052-06-10"
+            description = "Ono-Sendai Cyberspace VII"
+            unit_price  = 1000.0
+            quantity    = 1
+
+            [[sections]]
+            heading = "Travel"
+
+            [[sections.items]]
+            date        = "2052-06-21"
+            description = "Flight to Freeside"
+            unit_price  = 500.0
+            quantity    = 1
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let headings: Vec<&str> = invoice.sections().map(|(heading, _)| heading).collect();
+        assert_eq!(headings, vec!["Labour", "Expenses", "Hardware", "Travel"]);
+        assert_eq!(invoice.subtotal(), dec!(2700.0));
+        Ok(())
+    }
+
+    /// The invoice-wide markup applies to expenses that aren't reimbursable.
+    #[test]
+    fn test_parse_invoice_expense_markup() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id     = "1729"
+            issue_date     = "2052-06-30"
+            payment_terms  = "NET 30"
+            tax_rate       = 10.0
+            currency       = "AUD"
+            expense_markup = 10.0
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [[expenses]]
+            date        = "2052-06-21"
+            description = "Flight to Freeside (steerage)"
+            unit_price  = 500.0
+            quantity    = 1
+
+            [[expenses]]
+            date         = "2052-06-28"
+            description  = "A new liver"
+            unit_price   = 2500.0
+            quantity     = 1
+            reimbursable = true
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.labour[0].markup, None);
+        assert_eq!(invoice.expenses[0].markup, Some(dec!(10.0)));
+        assert_eq!(invoice.expenses[1].markup, None);
+        assert_eq!(invoice.subtotal(), dec!(4250.00));
+        assert_eq!(invoice.tax_amount(), dec!(175.00));
+        Ok(())
+    }
+
+    /// A section's markup applies to its items that aren't reimbursable.
+    #[test]
+    fn test_parse_invoice_section_markup() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id     = "1729"
+            issue_date     = "2052-06-30"
+            payment_terms  = "NET 30"
+            tax_rate       = 10.0
+            currency       = "AUD"
+            expense_markup = 5.0
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[sections]]
+            heading = "Travel"
+            markup  = 10.0
+
+            [[sections.items]]
+            date        = "2052-06-21"
+            description = "Flight to Freeside"
+            unit_price  = 500.0
+            quantity    = 1
+
+            [[sections.items]]
+            date         = "2052-06-22"
+            description  = "Taxi"
+            unit_price   = 40.0
+            quantity     = 1
+            reimbursable = true
+
+            [[sections]]
+            heading = "Hardware"
+
+            [[sections.items]]
+            date        = "2052-06-10"
+            description = "Ono-Sendai Cyberspace VII"
+            unit_price  = 1000.0
+            quantity    = 1
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let travel = &invoice.sections[0].items;
+        assert_eq!(travel[0].markup, Some(dec!(10.0)));
+        assert_eq!(travel[1].markup, None);
+        assert_eq!(invoice.sections[1].items[0].markup, None);
+        assert_eq!(invoice.subtotal(), dec!(1590.00));
+        Ok(())
+    }
+
+    /// A reimbursable item with a markup is rejected.
+    #[test]
+    fn test_parse_invoice_reimbursable_markup() {
+        let text = r#"
+            [metadata]
+            invoice_id     = "1729"
+            issue_date     = "2052-06-30"
+            payment_terms  = "NET 30"
+            tax_rate       = 10.0
+            currency       = "AUD"
+            
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            unit_price  = 300.0
+            quantity    = 4
+
+            [[expenses]]
+            date        = "2052-06-21"
+            description = "Flight to Freeside (steerage)"
+            unit_price  = 500.0
+            quantity    = 1
+
+            [[expenses]]
+            date         = "2052-06-28"
+            description  = "A new liver"
+            unit_price   = 2500.0
+            quantity     = 1
+            reimbursable = true
+            markup       = 5.0
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// The recipient's country defaults to the country in their address.
+    #[test]
+    fn test_parse_invoice_recipient_address() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [issuer.address]
+            street   = ["1 George Street"]
+            city     = "Sydney"
+            region   = "NSW"
+            postcode = "2000"
+            country  = "AU"
+
+            [recipient]
+            name       = "Wintermute"
+            company    = "Tessier-Ashpool S.A."
+            email      = "wmute@ta.sa"
+            tax_status = "export"
+
+            [recipient.address]
+            street   = ["Villa Straylight"]
+            city     = "Freeside"
+            postcode = "75001"
+            country  = "FR"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.recipient.country(), Some("FR"));
+        assert_eq!(
+            invoice.issuer.address.unwrap().lines(None),
+            vec!["1 George Street", "Sydney NSW 2000", "Australia"]
+        );
+        Ok(())
+    }
+
+    /// A recipient can be an individual, with optional billing details.
+    #[test]
+    fn test_parse_invoice_individual_recipient() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name      = "Henry Dorsett Case"
+            attention = "Accounts Payable"
+            email     = "case@chiba.jp"
+            cc        = ["molly@chiba.jp"]
+            reference = "PO-4417"
+            tax_id    = "T1234567890123"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.recipient.company, None);
+        assert_eq!(invoice.recipient.cc, vec!["molly@chiba.jp"]);
+        assert_eq!(invoice.recipient.reference.as_deref(), Some("PO-4417"));
+        Ok(())
+    }
+
+    /// An invoice can offer several payment methods instead of `payment`.
+    #[test]
+    fn test_parse_invoice_payment_methods() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 20.0
+            currency      = "EUR"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[payment_methods]]
+            method = "iban"
+            name   = "Fernando Borretti"
+            iban   = "CH93 0076 2011 6238 5295 7"
+            bic    = "FOOBAR"
+
+            [[payment_methods]]
+            method = "link"
+            url    = "https://pay.example.com/1729"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let methods = invoice.payment_methods();
+        assert_eq!(methods.len(), 2);
+        assert!(matches!(methods[0], PaymentMethod::Iban { .. }));
+        assert!(matches!(methods[1], PaymentMethod::Link { .. }));
+        Ok(())
+    }
+
+    /// An invoice without any way to pay is rejected.
+    #[test]
+    fn test_parse_invoice_no_payment_methods() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 20.0
+            currency      = "EUR"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// An issuer can be registered under other tax schemes.
+    #[test]
+    fn test_parse_invoice_tax_registration() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 20.0
+            currency      = "EUR"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            tax_registration = { scheme = "vat", number = "FR12345678901" }
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let registration = invoice.issuer.tax_registration().unwrap();
+        assert_eq!(registration.scheme.label(), "VAT ID");
+        assert_eq!(registration.number, "FR12345678901");
+        Ok(())
+    }
+
+    /// An issuer with both an ABN and a tax registration is rejected.
+    #[test]
+    fn test_parse_invoice_abn_and_tax_registration() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 20.0
+            currency      = "EUR"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn   = "123 456"
+            tax_registration = { scheme = "vat", number = "FR12345678901" }
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// Custom metadata fields keep their order.
+    #[test]
+    fn test_parse_invoice_custom_fields() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [[metadata.custom]]
+            label = "project"
+            value = "NEUROMANCER"
+
+            [[metadata.custom]]
+            label = "cost centre"
+            value = "CC-0451"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        let labels: Vec<&str> = invoice
+            .metadata
+            .custom
+            .iter()
+            .map(|field| field.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["project", "cost centre"]);
+        Ok(())
+    }
+
+    /// Line items can have an item code and multi-line details.
+    #[test]
+    fn test_parse_invoice_item_details() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            sku         = "HACK-01"
+            description = "Sense/Net (hacked)"
+            details     = """
+            - Bypassed the ICE
+            - Retrieved the Dixie Flatline
+            """
+            unit_price  = 300.0
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.labour[0].sku.as_deref(), Some("HACK-01"));
+        assert!(invoice.labour[0].details.is_some());
+        Ok(())
+    }
+
+    /// Line items take their price, unit, and tax code from the rate card,
+    /// unless they give their own.
+    #[test]
+    fn test_parse_invoice_rate_card() -> Fallible<()> {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [tax_codes.gst-free]
+            rate = 0.0
+
+            [rate_card.senior-dev]
+            unit_price = 300.0
+            unit       = "hours"
+
+            [rate_card.on-call]
+            unit_price = 150.0
+            tax_code   = "gst-free"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            rate        = "senior-dev"
+            quantity    = 4
+
+            [[labour]]
+            date        = "2052-06-04"
+            description = "Pager duty"
+            rate        = "on-call"
+            unit_price  = 100.0
+            quantity    = 2
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let invoice = Invoice::parse_string(text)?;
+        assert_eq!(invoice.labour[0].unit_price, dec!(300.0));
+        assert_eq!(invoice.labour[0].rate.as_deref(), Some("senior-dev"));
+        assert_eq!(invoice.labour[0].unit.as_deref(), Some("hours"));
+        assert_eq!(invoice.labour[1].unit_price, dec!(100.0));
+        assert_eq!(invoice.labour[1].tax_code.as_deref(), Some("gst-free"));
+        assert_eq!(invoice.subtotal(), dec!(1400.00));
+        assert_eq!(invoice.tax_amount(), dec!(120.00));
+        Ok(())
+    }
+
+    /// A line item referring to an unknown rate is rejected.
+    #[test]
+    fn test_parse_invoice_unknown_rate() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [tax_codes.gst-free]
+            rate = 0.0
+
+            [rate_card.senior-dev]
+            unit_price = 300.0
+            unit       = "hours"
+
+            [rate_card.on-call]
+            unit_price = 150.0
+            tax_code   = "gst-free"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            rate        = "junior-dev"
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let err = Invoice::parse_string(text).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line item `Sense/Net (hacked)` has an unknown rate: junior-dev"
+        );
+    }
+
+    /// A line item without a price or a rate is rejected.
+    #[test]
+    fn test_parse_invoice_missing_price() {
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let res = Invoice::parse_string(text);
+        assert!(res.is_err());
+    }
+
+    /// Rates can be loaded from a shared file, relative to the invoice.
+    #[test]
+    fn test_parse_invoice_rate_card_file() -> Fallible<()> {
+        let dir = tempfile::tempdir()?;
+        let rates = r#"
+            [senior-dev]
+            unit_price = 300.0
+            unit       = "hours"
+        "#;
+        std::fs::write(dir.path().join("rates.toml"), rates)?;
+        let text = r#"
+            [metadata]
+            invoice_id    = "1729"
+            issue_date    = "2052-06-30"
+            payment_terms = "NET 30"
+            tax_rate      = 10.0
+            currency      = "AUD"
+            rate_card_file = "rates.toml"
+
+            [issuer]
+            name  = "Fernando Borretti"
+            email = "fernando@borretti.me"
+            abn = "123 456"
+
+            [recipient]
+            name    = "Wintermute"
+            company = "Tessier-Ashpool S.A."
+            email   = "wmute@ta.sa"
+
+            [[labour]]
+            date        = "2052-06-03"
+            description = "Sense/Net (hacked)"
+            rate        = "senior-dev"
+            quantity    = 4
+
+            [payment]
+            name  = "Fernando Borretti"
+            bsb   = "999-999"
+            acct  = "9999 9999"
+            bank  = "Crédit Nuage de Genève"
+            swift = "FOOBAR"
+        "#;
+        let path = dir.path().join("invoice.toml");
+        std::fs::write(&path, text)?;
+        let invoice = Invoice::parse(&path)?;
+        assert_eq!(invoice.subtotal(), dec!(1200.00));
+        Ok(())
     }
 
     /// An invoice with no expenses is parsed correctly.
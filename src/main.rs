@@ -12,14 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod address;
+mod country;
+mod currency;
 mod error;
 mod html;
 mod invoice;
+mod markdown;
 mod pdf;
+mod rates;
+mod terms;
 
 use std::path::PathBuf;
 use std::process::ExitCode;
 
+use chrono::NaiveDate;
 use clap::Parser;
 use invoice::Invoice;
 
@@ -35,12 +42,15 @@ struct Args {
     input: PathBuf,
     /// Path to the output PDF file.
     output: PathBuf,
+    /// Add late charges accrued as of this date (YYYY-MM-DD).
+    #[arg(long)]
+    as_of: Option<NaiveDate>,
 }
 
 fn entrypoint() -> Fallible<()> {
     let args = Args::parse();
     let invoice: Invoice = Invoice::parse(&args.input)?;
-    generate_pdf(&invoice, &args.output)?;
+    generate_pdf(&invoice, args.as_of, &args.output)?;
     Ok(())
 }
 
@@ -0,0 +1,115 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use maud::Markup;
+use maud::PreEscaped;
+use pulldown_cmark::CowStr;
+use pulldown_cmark::Event;
+use pulldown_cmark::Parser;
+use pulldown_cmark::Tag;
+
+/// URL schemes links and images may use.
+const SAFE_SCHEMES: &[&str] = &["http:", "https:", "mailto:", "tel:"];
+
+/// Render Markdown to HTML. Raw HTML in the input is escaped, and links with
+/// unsafe URL schemes, like `javascript:`, are removed.
+pub fn render_markdown(text: &str) -> Markup {
+    let events = Parser::new(text).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+    let mut html: String = String::new();
+    pulldown_cmark::html::push_html(&mut html, events);
+    PreEscaped(html)
+}
+
+/// Replace a URL with an empty one unless it's relative or uses a safe
+/// scheme.
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    let lower: String = url.to_ascii_lowercase();
+    let scheme: Option<&str> = lower.split_once(':').map(|(scheme, _)| scheme);
+    let has_scheme: bool = scheme.is_some_and(|scheme| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    if !has_scheme || SAFE_SCHEMES.iter().any(|safe| lower.starts_with(safe)) {
+        url
+    } else {
+        CowStr::Borrowed("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(text: &str) -> String {
+        render_markdown(text).into_string()
+    }
+
+    /// Markdown is converted to HTML.
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(
+            render("Thanks for your **business**."),
+            "<p>Thanks for your <strong>business</strong>.</p>\n"
+        );
+    }
+
+    /// Raw HTML is escaped.
+    #[test]
+    fn test_render_markdown_escapes_html() {
+        assert_eq!(
+            render("Hi <script>alert(1)</script>"),
+            "<p>Hi &lt;script&gt;alert(1)&lt;/script&gt;</p>\n"
+        );
+    }
+
+    /// Links with unsafe schemes are removed.
+    #[test]
+    fn test_render_markdown_unsafe_links() {
+        assert_eq!(
+            render("[pay](javascript:alert(1))"),
+            "<p><a href=\"\">pay</a></p>\n"
+        );
+        assert_eq!(
+            render("[pay](https://example.com)"),
+            "<p><a href=\"https://example.com\">pay</a></p>\n"
+        );
+    }
+}
@@ -16,6 +16,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 
+use chrono::NaiveDate;
 use tempfile::tempdir;
 
 use crate::error::Fallible;
@@ -24,14 +25,18 @@ use crate::html::render_html;
 use crate::invoice::Invoice;
 
 /// Generate a PDF from an invoice.
-pub fn generate_pdf(invoice: &Invoice, output_path: &Path) -> Fallible<()> {
+pub fn generate_pdf(
+    invoice: &Invoice,
+    as_of: Option<NaiveDate>,
+    output_path: &Path,
+) -> Fallible<()> {
     // Create temporary directory
     let dir = tempdir()?;
     let dir_path: PathBuf = dir.path().to_path_buf().canonicalize()?;
 
     // Write HTML to temporary file
     let html_path = dir_path.join("invoice.html");
-    let html = render_html(invoice).into_string();
+    let html = render_html(invoice, as_of).into_string();
     std::fs::write(&html_path, html)?;
 
     // Run headless Chromium to generate PDF
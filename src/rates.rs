@@ -0,0 +1,150 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::error::Fallible;
+use crate::error::ScriptError;
+
+/// An exchange rate: one unit of `from` is worth `rate` units of `to`.
+#[derive(Debug, PartialEq)]
+struct ExchangeRate {
+    date: NaiveDate,
+    from: String,
+    to: String,
+    rate: Decimal,
+}
+
+/// Look up the exchange rate between two currencies in a CSV file, using the
+/// most recent rate on or before the given date.
+pub fn lookup_rate(path: &Path, from: &str, to: &str, date: NaiveDate) -> Fallible<Decimal> {
+    let text: String = std::fs::read_to_string(path)?;
+    let rates: Vec<ExchangeRate> = parse_rates(&text)?;
+    find_rate(&rates, from, to, date).ok_or_else(|| {
+        ScriptError::new(format!(
+            "no exchange rate from {from} to {to} on or before {date} in {}",
+            path.display()
+        ))
+    })
+}
+
+/// Parse a CSV file of exchange rates. Each line has the form
+/// `date,from,to,rate`. An optional header line, blank lines, and lines
+/// starting with `#` are skipped.
+fn parse_rates(text: &str) -> Fallible<Vec<ExchangeRate>> {
+    let mut rates: Vec<ExchangeRate> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == "date,from,to,rate" {
+            continue;
+        }
+        let error = || ScriptError::new(format!("invalid exchange rate on line {}", idx + 1));
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [date, from, to, rate] = fields.as_slice() else {
+            return Err(error());
+        };
+        rates.push(ExchangeRate {
+            date: NaiveDate::from_str(date).map_err(|_| error())?,
+            from: from.to_string(),
+            to: to.to_string(),
+            rate: Decimal::from_str(rate).map_err(|_| error())?,
+        });
+    }
+    Ok(rates)
+}
+
+/// Find the most recent rate on or before the given date. Rates in the
+/// opposite direction are inverted.
+fn find_rate(rates: &[ExchangeRate], from: &str, to: &str, date: NaiveDate) -> Option<Decimal> {
+    rates
+        .iter()
+        .filter(|r| r.date <= date)
+        .filter_map(|r| {
+            if r.from == from && r.to == to {
+                Some((r.date, r.rate))
+            } else if r.from == to && r.to == from && !r.rate.is_zero() {
+                Some((r.date, Decimal::ONE / r.rate))
+            } else {
+                None
+            }
+        })
+        .max_by_key(|(date, _)| *date)
+        .map(|(_, rate)| rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    const RATES: &str = "
+        date,from,to,rate
+        # Monthly rates.
+        2052-05-31,USD,AUD,1.50
+        2052-06-30,USD,AUD,1.52
+        2052-07-31,USD,AUD,1.55
+        2052-06-30,AUD,EUR,0.50
+    ";
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// The most recent rate on or before the date is used.
+    #[test]
+    fn test_find_rate_most_recent() -> Fallible<()> {
+        let rates = parse_rates(RATES)?;
+        assert_eq!(
+            find_rate(&rates, "USD", "AUD", date(2052, 6, 30)),
+            Some(dec!(1.52))
+        );
+        assert_eq!(
+            find_rate(&rates, "USD", "AUD", date(2052, 7, 15)),
+            Some(dec!(1.52))
+        );
+        Ok(())
+    }
+
+    /// Rates in the opposite direction are inverted.
+    #[test]
+    fn test_find_rate_inverse() -> Fallible<()> {
+        let rates = parse_rates(RATES)?;
+        assert_eq!(
+            find_rate(&rates, "EUR", "AUD", date(2052, 7, 1)),
+            Some(dec!(2))
+        );
+        Ok(())
+    }
+
+    /// There is no rate before the earliest entry.
+    #[test]
+    fn test_find_rate_missing() -> Fallible<()> {
+        let rates = parse_rates(RATES)?;
+        assert_eq!(find_rate(&rates, "USD", "AUD", date(2052, 1, 1)), None);
+        assert_eq!(find_rate(&rates, "GBP", "AUD", date(2052, 7, 1)), None);
+        Ok(())
+    }
+
+    /// Malformed lines are rejected.
+    #[test]
+    fn test_parse_rates_invalid() {
+        assert!(parse_rates("2052-06-30,USD,AUD").is_err());
+        assert!(parse_rates("2052-06-30,USD,AUD,abc").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2026 Fernando Borretti
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::Datelike;
+use chrono::Days;
+use chrono::NaiveDate;
+
+/// Payment terms, which determine when payment is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentTerms {
+    /// Payment is due on the issue date.
+    OnReceipt,
+    /// Payment is due a number of days after the issue date, e.g. `NET 30`.
+    Net(u64),
+    /// Payment is due a number of days after the end of the month the
+    /// invoice is issued in, e.g. `EOM` or `NET 30 EOM`.
+    EndOfMonth(u64),
+}
+
+impl PaymentTerms {
+    /// Parse payment terms like `NET 30`, `EOM`, `30 days after EOM`, or
+    /// `due on receipt`. Returns `None` if the terms aren't recognised.
+    pub fn parse(text: &str) -> Option<PaymentTerms> {
+        let tokens: Vec<String> = tokenize(text);
+        let tokens: Vec<&str> = tokens
+            .iter()
+            .map(String::as_str)
+            .filter(|t| !matches!(*t, "+" | "DAY" | "DAYS"))
+            .collect();
+        let days = |n: &str| n.parse::<u64>().ok();
+        match tokens.as_slice() {
+            ["DUE", "ON", "RECEIPT"]
+            | ["ON", "RECEIPT"]
+            | ["DUE", "UPON", "RECEIPT"]
+            | ["UPON", "RECEIPT"] => Some(PaymentTerms::OnReceipt),
+            ["EOM"] | ["NET", "EOM"] => Some(PaymentTerms::EndOfMonth(0)),
+            ["NET", n, "EOM"] | ["EOM", n] | [n, "AFTER", "EOM"] | ["NET", n, "AFTER", "EOM"] => {
+                days(n).map(PaymentTerms::EndOfMonth)
+            }
+            ["NET", n] | [n] => days(n).map(PaymentTerms::Net),
+            _ => None,
+        }
+    }
+
+    /// The date payment is due, for an invoice issued on the given date.
+    /// Returns `None` if the date is out of range.
+    pub fn due_date(&self, issue_date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            PaymentTerms::OnReceipt => Some(issue_date),
+            PaymentTerms::Net(days) => issue_date.checked_add_days(Days::new(*days)),
+            PaymentTerms::EndOfMonth(days) => {
+                end_of_month(issue_date).checked_add_days(Days::new(*days))
+            }
+        }
+    }
+}
+
+/// Split payment terms into upper-case words, numbers, and `+` signs.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    for c in text.to_uppercase().chars() {
+        let boundary: bool = match current.chars().last() {
+            Some(last) => !c.is_alphanumeric() || last.is_ascii_digit() != c.is_ascii_digit(),
+            None => false,
+        };
+        if boundary {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if c == '+' {
+            tokens.push(c.to_string());
+        } else if c.is_alphanumeric() {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// The last day of the month the date is in.
+fn end_of_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Net terms count days from the issue date.
+    #[test]
+    fn test_parse_net() {
+        assert_eq!(PaymentTerms::parse("NET 30"), Some(PaymentTerms::Net(30)));
+        assert_eq!(PaymentTerms::parse("net30"), Some(PaymentTerms::Net(30)));
+        assert_eq!(PaymentTerms::parse("14 days"), Some(PaymentTerms::Net(14)));
+    }
+
+    /// End of month terms count days from the end of the issue month.
+    #[test]
+    fn test_parse_end_of_month() {
+        assert_eq!(
+            PaymentTerms::parse("EOM"),
+            Some(PaymentTerms::EndOfMonth(0))
+        );
+        assert_eq!(
+            PaymentTerms::parse("NET 30 EOM"),
+            Some(PaymentTerms::EndOfMonth(30))
+        );
+        assert_eq!(
+            PaymentTerms::parse("EOM+15"),
+            Some(PaymentTerms::EndOfMonth(15))
+        );
+        assert_eq!(
+            PaymentTerms::parse("20 days after EOM"),
+            Some(PaymentTerms::EndOfMonth(20))
+        );
+    }
+
+    /// Payment on receipt is due on the issue date.
+    #[test]
+    fn test_parse_on_receipt() {
+        assert_eq!(
+            PaymentTerms::parse("Due on receipt"),
+            Some(PaymentTerms::OnReceipt)
+        );
+    }
+
+    /// Unrecognised terms aren't parsed.
+    #[test]
+    fn test_parse_unknown() {
+        assert_eq!(PaymentTerms::parse("whenever"), None);
+        assert_eq!(PaymentTerms::parse("NET thirty"), None);
+    }
+
+    /// Due dates are computed from the issue date.
+    #[test]
+    fn test_due_date() {
+        let issued = date(2052, 2, 10);
+        assert_eq!(PaymentTerms::OnReceipt.due_date(issued), Some(issued));
+        assert_eq!(
+            PaymentTerms::Net(30).due_date(issued),
+            Some(date(2052, 3, 11))
+        );
+        assert_eq!(
+            PaymentTerms::EndOfMonth(0).due_date(issued),
+            Some(date(2052, 2, 29))
+        );
+        assert_eq!(
+            PaymentTerms::EndOfMonth(30).due_date(date(2052, 12, 5)),
+            Some(date(2053, 1, 30))
+        );
+    }
+
+    /// Due dates out of range are `None`.
+    #[test]
+    fn test_due_date_out_of_range() {
+        let issued = date(2052, 2, 10);
+        assert_eq!(PaymentTerms::Net(999_999_999).due_date(issued), None);
+        assert_eq!(PaymentTerms::EndOfMonth(u64::MAX).due_date(issued), None);
+    }
+}